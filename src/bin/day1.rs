@@ -1,46 +1,720 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use aoc2022::parse::{self, ParseError};
+
 fn main() {
     let input = include_str!("../../inputs/input1.txt");
-    println!("Day1 Part1: {}", day1_part1(input));
-    println!("Day1 Part2: {}", day1_part2(input));
+    let args = std::env::args().collect::<Vec<String>>();
+    let answers = if args.iter().any(|arg| arg == "--wide") {
+        [
+            max_calories::<u128>(input).map(|total| total.to_string()),
+            top_n_sum::<u128>(input, 3).map(|total| total.to_string()),
+        ]
+    } else {
+        [
+            day1_part1(input).map(|total| total.to_string()),
+            day1_part2(input).map(|total| total.to_string()),
+        ]
+    };
+    for (part, answer) in (1..).zip(answers) {
+        match answer {
+            Ok(total) => println!("Day1 Part{part}: {total}"),
+            Err(err) => println!("Day1 Part{part}: {err}"),
+        }
+    }
+
+    if let Some(n) = args.iter().position(|arg| arg == "--top") {
+        let n = args.get(n + 1).and_then(|n| n.parse().ok()).unwrap_or(3);
+        let names = labels(&parse_elves(input).unwrap());
+        match top_n_calories(input, n) {
+            Ok(top) => {
+                for (elf, total) in top {
+                    println!("{}: {total}", names[elf]);
+                }
+            }
+            Err(err) => println!("{err}"),
+        }
+    }
+
+    if args.iter().any(|arg| arg == "--balance") {
+        let elves = parse_elves(input).unwrap();
+        let totals = elves.iter().map(Elf::total).collect::<Vec<usize>>();
+        let names = labels(&elves);
+        let plan = plan_redistribution(&elves);
+        println!();
+        for t in &plan.transfers {
+            println!(
+                "move {} from {} to {}",
+                t.calories, names[t.from], names[t.to]
+            );
+        }
+        println!(
+            "Spread: {} -> {} with {} transfers",
+            spread(&totals),
+            plan.spread(),
+            plan.transfers.len()
+        );
+    }
+
+    for (flag, budget) in [
+        ("--exactly", Budget::Exactly as fn(usize) -> Budget),
+        ("--at-least", Budget::AtLeast),
+    ] {
+        let Some(n) = args.iter().position(|arg| arg == flag) else {
+            continue;
+        };
+        let target = args[n + 1].parse().unwrap();
+        let elves = parse_elves(input).unwrap();
+        let totals = elves.iter().map(Elf::total).collect::<Vec<usize>>();
+        match subset_query(&totals, budget(target)) {
+            Ok(Some(chosen)) => {
+                let sum = chosen.iter().map(|&e| totals[e]).sum::<usize>();
+                let names = chosen.iter().map(|&e| elves[e].label(e));
+                println!("{}: {sum}", names.collect::<Vec<String>>().join(", "));
+            }
+            Ok(None) => println!("no elves add up to {target}"),
+            Err(err) => println!("{err}"),
+        }
+    }
+
+    if args.iter().any(|arg| arg == "--report") {
+        println!();
+        print!("{}", InventoryStats::new(&parse_elves(input).unwrap()));
+    }
 }
 
-fn day1_part1(input: &str) -> usize {
-    let mut most_calories = 0;
-    let mut cur_sum = 0;
+#[derive(Debug, PartialEq, Eq)]
+enum CalorieError {
+    Parse(ParseError),
+    TooFewElves { wanted: usize, found: usize },
+    ElfOverflow { elf: usize, line: usize },
+    SumOverflow,
+    SearchTooLarge { elves: usize, target: usize },
+}
 
-    for line in input.lines() {
-        match line {
-            "" => {
-                most_calories = most_calories.max(cur_sum);
-                cur_sum = 0;
+impl fmt::Display for CalorieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "{err}"),
+            Self::TooFewElves { wanted, found } => {
+                write!(f, "asked for the top {wanted} elves but only {found} exist")
+            }
+            Self::ElfOverflow { elf, line } => {
+                write!(
+                    f,
+                    "calories of elf {elf} overflow at line {line}; try --wide"
+                )
+            }
+            Self::SumOverflow => write!(f, "sum of the top elves overflows; try --wide"),
+            Self::SearchTooLarge { elves, target } => {
+                write!(f, "cannot search {elves} elves for a total of {target}")
             }
-            _ => cur_sum += line.parse::<usize>().unwrap(),
         }
     }
+}
 
-    most_calories.max(cur_sum)
+impl From<ParseError> for CalorieError {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
 }
 
-fn day1_part2(input: &str) -> usize {
-    let mut calories = Vec::new();
-    let mut cur_sum = 0;
+/// Integer types calories can be counted in.
+trait Calories: Copy + Ord + Default + FromStr + fmt::Display {
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+}
 
-    for line in input.lines() {
-        match line {
-            "" => {
-                calories.push(cur_sum);
-                cur_sum = 0;
+macro_rules! impl_calories {
+    ($($t:ty),*) => {
+        $(impl Calories for $t {
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
             }
-            _ => cur_sum += line.parse::<usize>().unwrap(),
+        })*
+    };
+}
+
+impl_calories!(usize, u128);
+
+/// Elves and their items, checking that each elf's total fits in `T`.
+///
+/// Besides plain numbers a group may open with an `# elf: <name>` header, and
+/// lines starting with `//` are ignored anywhere.
+fn parse_groups<T: Calories>(input: &str) -> Result<Vec<Elf<T>>, CalorieError> {
+    let mut elves = Vec::new();
+
+    for block in parse::blocks(input) {
+        let mut elf: Option<Elf<T>> = None;
+        let mut total = T::default();
+
+        for mut line in block.parsers() {
+            if line.try_literal("//") {
+                continue;
+            }
+            if line.try_literal("#") {
+                if elf.is_some() {
+                    return Err(line.error("elf header must start its group").into());
+                }
+                line.take_while(char::is_whitespace);
+                line.literal("elf:")?;
+                line.take_while(char::is_whitespace);
+                let name = line.clone().rest().trim_end();
+                if name.is_empty() {
+                    return Err(line.error("expected an elf name").into());
+                }
+                elf = Some(Elf {
+                    name: Some(name.to_string()),
+                    items: Vec::new(),
+                });
+                continue;
+            }
+
+            let calories = line.integer::<T>()?;
+            line.end()?;
+            total = total
+                .checked_add(calories)
+                .ok_or(CalorieError::ElfOverflow {
+                    elf: elves.len(),
+                    line: line.line(),
+                })?;
+            elf.get_or_insert_with(|| Elf::new(Vec::new()))
+                .items
+                .push(calories);
         }
+
+        // a group of nothing but comments is not an elf
+        elves.extend(elf);
     }
 
-    calories.push(cur_sum);
+    Ok(elves)
+}
+
+fn checked_totals<T: Calories>(input: &str) -> Result<Vec<T>, CalorieError> {
+    Ok(parse_groups::<T>(input)?
+        .into_iter()
+        .map(|elf| {
+            elf.items
+                .into_iter()
+                .fold(T::default(), |sum, c| sum.checked_add(c).unwrap())
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Elf<T = usize> {
+    name: Option<String>,
+    items: Vec<T>,
+}
+
+impl<T> Elf<T> {
+    fn new(items: Vec<T>) -> Self {
+        Self { name: None, items }
+    }
 
-    calories.sort_unstable();
-    calories.reverse();
+    /// The elf's name if the input gave one, otherwise its index.
+    fn label(&self, index: usize) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("elf {index}"),
+        }
+    }
+}
+
+impl Elf {
+    fn total(&self) -> usize {
+        self.items.iter().sum()
+    }
+}
+
+fn parse_elves(input: &str) -> Result<Vec<Elf>, CalorieError> {
+    parse_groups(input)
+}
+
+fn labels(elves: &[Elf]) -> Vec<String> {
+    elves
+        .iter()
+        .enumerate()
+        .map(|(i, elf)| elf.label(i))
+        .collect()
+}
+
+fn max_calories<T: Calories>(input: &str) -> Result<T, CalorieError> {
+    Ok(checked_totals::<T>(input)?
+        .into_iter()
+        .max()
+        .unwrap_or_default())
+}
 
-    calories[..3].iter().sum()
+fn top_n_sum<T: Calories>(input: &str, n: usize) -> Result<T, CalorieError> {
+    top_n(checked_totals::<T>(input)?, n)?
+        .into_iter()
+        .try_fold(T::default(), |sum, (_, total)| sum.checked_add(total))
+        .ok_or(CalorieError::SumOverflow)
+}
+
+fn day1_part1(input: &str) -> Result<usize, CalorieError> {
+    max_calories(input)
+}
+
+fn day1_part2(input: &str) -> Result<usize, CalorieError> {
+    top_n_sum(input, 3)
+}
+
+/// The `n` elves carrying the most, as `(elf index, total)` heaviest first.
+fn top_n_calories(input: &str, n: usize) -> Result<Vec<(usize, usize)>, CalorieError> {
+    top_n(checked_totals(input)?, n)
+}
+
+fn top_n<T: Calories>(totals: Vec<T>, n: usize) -> Result<Vec<(usize, T)>, CalorieError> {
+    if totals.len() < n {
+        return Err(CalorieError::TooFewElves {
+            wanted: n,
+            found: totals.len(),
+        });
+    }
+
+    // min-heap of the best n so far; on equal totals the later elf goes first
+    let mut heap = BinaryHeap::with_capacity(n + 1);
+    for (elf, total) in totals.into_iter().enumerate() {
+        heap.push(Reverse((total, Reverse(elf))));
+        if heap.len() > n {
+            heap.pop();
+        }
+    }
+
+    Ok(heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((total, Reverse(elf)))| (elf, total))
+        .collect())
+}
+
+const HISTOGRAM_BUCKETS: usize = 10;
+const HISTOGRAM_WIDTH: usize = 40;
+
+#[derive(Debug)]
+struct InventoryStats {
+    totals: Vec<usize>,
+    item_counts: Vec<usize>,
+    labels: Vec<String>,
+}
+
+impl InventoryStats {
+    fn new(elves: &[Elf]) -> Self {
+        Self {
+            totals: elves.iter().map(Elf::total).collect(),
+            item_counts: elves.iter().map(|elf| elf.items.len()).collect(),
+            labels: labels(elves),
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        self.totals.iter().map(|&t| t as f64).sum::<f64>() / self.totals.len() as f64
+    }
+
+    fn median(&self) -> f64 {
+        let mut sorted = self.totals.clone();
+        sorted.sort_unstable();
+        let mid = sorted.len() / 2;
+        if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] as f64 + sorted[mid] as f64) / 2.0
+        } else {
+            sorted[mid] as f64
+        }
+    }
+
+    /// Population standard deviation of the totals.
+    fn std_dev(&self) -> f64 {
+        let mean = self.mean();
+        let variance = self
+            .totals
+            .iter()
+            .map(|&t| (t as f64 - mean).powi(2))
+            .sum::<f64>()
+            / self.totals.len() as f64;
+        variance.sqrt()
+    }
+
+    /// Index of the heaviest elf; the first one wins ties.
+    fn heaviest(&self) -> usize {
+        (0..self.totals.len())
+            .max_by_key(|&i| (self.totals[i], Reverse(i)))
+            .unwrap()
+    }
+
+    /// Index of the lightest elf; the first one wins ties.
+    fn lightest(&self) -> usize {
+        (0..self.totals.len())
+            .min_by_key(|&i| (self.totals[i], i))
+            .unwrap()
+    }
+
+    /// Bucket ranges and how many elves fall in each; the last range is cut
+    /// short rather than run past `usize::MAX`.
+    fn histogram(&self) -> Vec<(usize, usize, usize)> {
+        let min = *self.totals.iter().min().unwrap();
+        let max = *self.totals.iter().max().unwrap();
+        let width = ((max - min) / HISTOGRAM_BUCKETS + 1).max(1);
+        let buckets = (max - min) / width + 1;
+
+        let mut counts = vec![0; buckets];
+        for &t in &self.totals {
+            counts[(t - min) / width] += 1;
+        }
+
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| {
+                // i * width never exceeds max - min
+                let low = min + i * width;
+                (low, low.saturating_add(width - 1), count)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for InventoryStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.totals.is_empty() {
+            return writeln!(f, "Elves: 0");
+        }
+
+        let items = &self.item_counts;
+        writeln!(f, "Elves: {}", self.totals.len())?;
+        writeln!(
+            f,
+            "Items per elf: min {} / mean {:.2} / max {}",
+            items.iter().min().unwrap(),
+            items.iter().sum::<usize>() as f64 / items.len() as f64,
+            items.iter().max().unwrap()
+        )?;
+        writeln!(f, "Mean total: {:.2}", self.mean())?;
+        writeln!(f, "Median total: {:.1}", self.median())?;
+        writeln!(f, "Standard deviation: {:.2}", self.std_dev())?;
+        let (heaviest, lightest) = (self.heaviest(), self.lightest());
+        writeln!(
+            f,
+            "Heaviest: {} ({})",
+            self.labels[heaviest], self.totals[heaviest]
+        )?;
+        writeln!(
+            f,
+            "Lightest: {} ({})",
+            self.labels[lightest], self.totals[lightest]
+        )?;
+
+        writeln!(f, "Histogram:")?;
+        let histogram = self.histogram();
+        let tallest = histogram.iter().map(|&(_, _, c)| c).max().unwrap();
+        let label_width = histogram.last().unwrap().1.to_string().len();
+        for (low, high, count) in histogram {
+            let bar = "#".repeat((count * HISTOGRAM_WIDTH).div_ceil(tallest));
+            writeln!(
+                f,
+                "{low:>label_width$}-{high:>label_width$} | {bar} {count}"
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Largest `elves ^ items` search space the planner will explore exhaustively.
+const EXACT_PLAN_LIMIT: u64 = 10_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Transfer {
+    calories: usize,
+    from: usize,
+    to: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Plan {
+    transfers: Vec<Transfer>,
+    totals: Vec<usize>,
+}
+
+impl Plan {
+    fn spread(&self) -> usize {
+        spread(&self.totals)
+    }
+}
+
+fn spread(totals: &[usize]) -> usize {
+    match (totals.iter().max(), totals.iter().min()) {
+        (Some(max), Some(min)) => max - min,
+        _ => 0,
+    }
+}
+
+/// Move whole items between elves so the heaviest and lightest totals end up
+/// as close as possible.
+///
+/// Small inputs are searched exhaustively, preferring fewer transfers among
+/// equally balanced results. Larger ones use a greedy heuristic: the lightest
+/// elf takes an item from the heaviest elf that has one smaller than the gap
+/// between them, picking the item that best halves that gap. Every such move
+/// lowers the sum of squared totals without widening the spread, so it always
+/// terminates, but it can stop in a local optimum.
+fn plan_redistribution(elves: &[Elf]) -> Plan {
+    // (calories, original owner), heaviest first so the search prunes early
+    let mut items: Vec<(usize, usize)> = elves
+        .iter()
+        .enumerate()
+        .flat_map(|(elf, e)| e.items.iter().map(move |&c| (c, elf)))
+        .collect();
+    items.sort_by_key(|&(c, elf)| (Reverse(c), elf));
+
+    let exact = u32::try_from(items.len())
+        .ok()
+        .and_then(|m| (elves.len() as u64).checked_pow(m))
+        .is_some_and(|space| space <= EXACT_PLAN_LIMIT);
+    let owners = if exact {
+        exact_owners(&items, elves.len())
+    } else {
+        greedy_owners(&items, elves.len())
+    };
+
+    let mut totals = vec![0; elves.len()];
+    let mut transfers = Vec::new();
+    for (&(calories, from), &to) in items.iter().zip(&owners) {
+        totals[to] += calories;
+        if from != to {
+            transfers.push(Transfer { calories, from, to });
+        }
+    }
+    transfers.sort_by_key(|t| (t.from, t.to, Reverse(t.calories)));
+
+    Plan { transfers, totals }
+}
+
+struct ExactSearch<'a> {
+    items: &'a [(usize, usize)],
+    /// Calories still to be placed from item `i` onwards.
+    remaining: Vec<usize>,
+    loads: Vec<usize>,
+    owners: Vec<usize>,
+    best: (usize, usize, Vec<usize>),
+}
+
+impl ExactSearch<'_> {
+    fn search(&mut self, i: usize, moves: usize) {
+        let max = *self.loads.iter().max().unwrap();
+        let min = *self.loads.iter().min().unwrap();
+        // the lightest elf can at most receive everything that is left
+        let bound = max.saturating_sub(min + self.remaining[i]);
+        if (bound, moves) >= (self.best.0, self.best.1) {
+            return;
+        }
+        if i == self.items.len() {
+            self.best = (max - min, moves, self.owners.clone());
+            return;
+        }
+
+        let (calories, original) = self.items[i];
+        let order =
+            std::iter::once(original).chain((0..self.loads.len()).filter(|&e| e != original));
+        for elf in order {
+            self.loads[elf] += calories;
+            self.owners.push(elf);
+            self.search(i + 1, moves + usize::from(elf != original));
+            self.owners.pop();
+            self.loads[elf] -= calories;
+        }
+    }
+}
+
+fn exact_owners(items: &[(usize, usize)], elves: usize) -> Vec<usize> {
+    if elves == 0 {
+        return Vec::new();
+    }
+    let mut remaining = vec![0; items.len() + 1];
+    for i in (0..items.len()).rev() {
+        remaining[i] = remaining[i + 1] + items[i].0;
+    }
+
+    let mut search = ExactSearch {
+        items,
+        remaining,
+        loads: vec![0; elves],
+        owners: Vec::with_capacity(items.len()),
+        best: (usize::MAX, usize::MAX, Vec::new()),
+    };
+    search.search(0, 0);
+    search.best.2
+}
+
+fn greedy_owners(items: &[(usize, usize)], elves: usize) -> Vec<usize> {
+    let mut owners: Vec<usize> = items.iter().map(|&(_, elf)| elf).collect();
+    let mut loads = vec![0; elves];
+    let mut held: Vec<Vec<usize>> = vec![Vec::new(); elves];
+    for (i, &(calories, elf)) in items.iter().enumerate() {
+        loads[elf] += calories;
+        held[elf].push(i);
+    }
+
+    loop {
+        let light = (0..elves).min_by_key(|&e| loads[e]).unwrap();
+        let mut donors = (0..elves).collect::<Vec<usize>>();
+        donors.sort_by_key(|&e| Reverse(loads[e]));
+
+        let best_move = donors.into_iter().find_map(|donor| {
+            let gap = loads[donor] - loads[light];
+            (0..held[donor].len())
+                .filter(|&p| items[held[donor][p]].0 < gap)
+                .min_by_key(|&p| gap.abs_diff(2 * items[held[donor][p]].0))
+                .map(|p| (donor, p))
+        });
+        let Some((donor, pos)) = best_move else {
+            break;
+        };
+
+        let item = held[donor].swap_remove(pos);
+        loads[donor] -= items[item].0;
+        loads[light] += items[item].0;
+        held[light].push(item);
+        owners[item] = light;
+    }
+
+    owners
+}
+
+/// Most memory, in bits, the exact DP may use: one bit per elf and sum for the
+/// `taken` table plus a `usize` per sum for `fewest`.
+const SUBSET_DP_LIMIT: usize = 1 << 28;
+/// Meet-in-the-middle enumerates `2^(elves / 2)` subsets per half.
+const SUBSET_MITM_MAX_ELVES: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Budget {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+/// One smallest set of elves, by index, whose totals meet `budget`.
+fn subset_query(totals: &[usize], budget: Budget) -> Result<Option<Vec<usize>>, CalorieError> {
+    let mut elves = match budget {
+        // taking the heaviest elves first always gives the fewest
+        Budget::AtLeast(target) => {
+            let mut order = (0..totals.len()).collect::<Vec<usize>>();
+            order.sort_by_key(|&e| (Reverse(totals[e]), e));
+            let mut sum = 0u128;
+            let mut chosen = Vec::new();
+            for e in order {
+                if sum >= target as u128 {
+                    break;
+                }
+                sum += totals[e] as u128;
+                chosen.push(e);
+            }
+            (sum >= target as u128).then_some(chosen)
+        }
+        Budget::Exactly(target) => {
+            if subset_dp_fits(totals.len(), target) {
+                subset_dp(totals, target)
+            } else if totals.len() <= SUBSET_MITM_MAX_ELVES {
+                subset_mitm(totals, target)
+            } else {
+                return Err(CalorieError::SearchTooLarge {
+                    elves: totals.len(),
+                    target,
+                });
+            }
+        }
+    };
+
+    if let Some(elves) = elves.as_mut() {
+        elves.sort_unstable();
+    }
+    Ok(elves)
+}
+
+/// Whether [`subset_dp`] for `elves` and `target` fits in [`SUBSET_DP_LIMIT`].
+fn subset_dp_fits(elves: usize, target: usize) -> bool {
+    let bits_per_sum = elves.saturating_add(usize::BITS as usize);
+    bits_per_sum.saturating_mul(target.saturating_add(1)) <= SUBSET_DP_LIMIT
+}
+
+/// 0/1 knapsack over sums `0..=target`, counting elves instead of value.
+fn subset_dp(totals: &[usize], target: usize) -> Option<Vec<usize>> {
+    let width = target + 1;
+    let mut fewest = vec![usize::MAX; width];
+    fewest[0] = 0;
+    // taken[e * width + s]: elf e is part of the best way to reach s using elves 0..=e
+    let mut taken = vec![0u64; (totals.len() * width).div_ceil(64)];
+
+    for (e, &total) in totals.iter().enumerate() {
+        for s in (total..width).rev() {
+            let with = fewest[s - total].saturating_add(1);
+            if with < fewest[s] {
+                fewest[s] = with;
+                let bit = e * width + s;
+                taken[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+    }
+
+    if fewest[target] == usize::MAX {
+        return None;
+    }
+
+    let mut elves = Vec::new();
+    let mut s = target;
+    for e in (0..totals.len()).rev() {
+        let bit = e * width + s;
+        if taken[bit / 64] & (1 << (bit % 64)) != 0 {
+            elves.push(e);
+            s -= totals[e];
+        }
+    }
+    Some(elves)
+}
+
+/// Smallest subset per reachable sum of `totals`, as a bitmask.
+fn half_sums(totals: &[usize]) -> HashMap<u128, u64> {
+    let mut sums: HashMap<u128, u64> = HashMap::new();
+    for mask in 0..1u64 << totals.len() {
+        let sum = (0..totals.len())
+            .filter(|&e| mask & (1 << e) != 0)
+            .map(|e| totals[e] as u128)
+            .sum();
+        sums.entry(sum)
+            .and_modify(|best| {
+                if mask.count_ones() < best.count_ones() {
+                    *best = mask;
+                }
+            })
+            .or_insert(mask);
+    }
+    sums
+}
+
+/// Split the elves in two, enumerate each half and match up the sums.
+fn subset_mitm(totals: &[usize], target: usize) -> Option<Vec<usize>> {
+    let (left, right) = totals.split_at(totals.len() / 2);
+    let left_sums = half_sums(left);
+    let right_sums = half_sums(right);
+
+    let (l, r) = left_sums
+        .iter()
+        .filter_map(|(&sum, &l)| {
+            let r = *right_sums.get(&(target as u128).checked_sub(sum)?)?;
+            Some((l, r))
+        })
+        .min_by_key(|&(l, r)| (l.count_ones() + r.count_ones(), l, r))?;
+
+    let from_left = (0..left.len()).filter(|&e| l & (1 << e) != 0);
+    let from_right = (0..right.len())
+        .filter(|&e| r & (1 << e) != 0)
+        .map(|e| e + left.len());
+    Some(from_left.chain(from_right).collect())
 }
 
 #[cfg(test)]
@@ -64,11 +738,193 @@ mod test {
 
     #[test]
     fn part1_test() {
-        assert_eq!(day1_part1(INPUT), 24000);
+        assert_eq!(day1_part1(INPUT), Ok(24000));
     }
 
     #[test]
     fn part2_test() {
-        assert_eq!(day1_part2(INPUT), 45000);
+        assert_eq!(day1_part2(INPUT), Ok(45000));
+    }
+
+    #[test]
+    fn report_test() {
+        let elves = parse_elves(INPUT).unwrap();
+        assert_eq!(elves[0].items, vec![1000, 2000, 3000]);
+
+        let stats = InventoryStats::new(&elves);
+        assert_eq!(stats.mean(), 11000.0);
+        assert_eq!(stats.median(), 10000.0);
+        assert!((stats.std_dev() - 6985.70).abs() < 0.01);
+        assert_eq!((stats.heaviest(), stats.lightest()), (3, 1));
+        let histogram = stats.histogram();
+        assert_eq!(histogram.len(), HISTOGRAM_BUCKETS);
+        assert_eq!(histogram[0], (4000, 6000, 2));
+        assert_eq!(histogram[9], (22009, 24009, 1));
+    }
+
+    #[test]
+    fn overflow_test() {
+        let huge = format!("1\n\n{}\n1\n\n2", usize::MAX);
+        assert_eq!(
+            checked_totals::<usize>(&huge),
+            Err(CalorieError::ElfOverflow { elf: 1, line: 4 })
+        );
+        assert_eq!(
+            day1_part1(&huge).unwrap_err().to_string(),
+            "calories of elf 1 overflow at line 4; try --wide"
+        );
+        assert_eq!(max_calories::<u128>(&huge), Ok(usize::MAX as u128 + 1));
+        assert_eq!(top_n_sum::<u128>(&huge, 2), Ok(usize::MAX as u128 + 3));
+
+        let max = format!("{}\n\n{}", u128::MAX, u128::MAX);
+        assert_eq!(max_calories::<u128>(&max), Ok(u128::MAX));
+        assert_eq!(top_n_sum::<u128>(&max, 2), Err(CalorieError::SumOverflow));
+
+        let extremes = format!("0\n\n{}", usize::MAX);
+        let stats = InventoryStats::new(&parse_elves(&extremes).unwrap());
+        assert_eq!(stats.median(), usize::MAX as f64 / 2.0);
+        let histogram = stats.histogram();
+        assert_eq!(histogram.first().map(|b| (b.0, b.2)), Some((0, 1)));
+        assert_eq!(histogram.last().map(|b| (b.1, b.2)), Some((usize::MAX, 1)));
+        assert!(stats.to_string().contains("Median total"));
+        assert!(matches!(
+            max_calories::<usize>(&max),
+            Err(CalorieError::Parse(ParseError { line: 1, .. }))
+        ));
+    }
+
+    #[test]
+    fn balance_test() {
+        let elves = vec![Elf::new(vec![10, 1]), Elf::new(vec![2]), Elf::new(vec![3])];
+        let plan = plan_redistribution(&elves);
+        assert_eq!(plan.totals, vec![10, 3, 3]);
+        assert_eq!(
+            plan.transfers,
+            vec![Transfer {
+                calories: 1,
+                from: 0,
+                to: 1
+            }]
+        );
+
+        let plan = plan_redistribution(&parse_elves(INPUT).unwrap());
+        assert_eq!(plan.spread(), 0);
+        assert_eq!(plan.transfers.len(), 4);
+
+        // too big to search exactly, falls back to the heuristic
+        let elves = (0..40)
+            .map(|e| Elf::new((1..=e % 7 + 1).map(|i| i * 100 + e).collect()))
+            .collect::<Vec<Elf>>();
+        let before = elves.iter().map(Elf::total).collect::<Vec<usize>>();
+        let plan = plan_redistribution(&elves);
+        assert!(plan.spread() < spread(&before));
+        assert_eq!(plan.totals.iter().sum::<usize>(), before.iter().sum());
+        let mut after = before.clone();
+        for t in &plan.transfers {
+            after[t.from] -= t.calories;
+            after[t.to] += t.calories;
+        }
+        assert_eq!(after, plan.totals);
+    }
+
+    #[test]
+    fn subset_test() {
+        let totals = checked_totals::<usize>(INPUT).unwrap();
+        assert_eq!(
+            subset_query(&totals, Budget::Exactly(21000)),
+            Ok(Some(vec![2, 4]))
+        );
+        assert_eq!(
+            subset_query(&totals, Budget::Exactly(45000)),
+            Ok(Some(vec![2, 3, 4]))
+        );
+        assert_eq!(subset_query(&totals, Budget::Exactly(1)), Ok(None));
+        assert_eq!(subset_query(&totals, Budget::Exactly(0)), Ok(Some(vec![])));
+        assert_eq!(
+            subset_query(&totals, Budget::AtLeast(30000)),
+            Ok(Some(vec![2, 3]))
+        );
+        assert_eq!(subset_query(&totals, Budget::AtLeast(60000)), Ok(None));
+
+        // too large for the table, so the halves are matched instead
+        let scale = 1_000_000_000_000;
+        let huge = totals.iter().map(|t| t * scale).collect::<Vec<usize>>();
+        assert_eq!(
+            subset_query(&huge, Budget::Exactly(21000 * scale)),
+            Ok(Some(vec![2, 4]))
+        );
+        assert_eq!(subset_mitm(&totals, 45000).map(|e| e.len()), Some(3));
+
+        // a lone elf still needs a `fewest` entry per sum
+        assert!(subset_dp_fits(5, 45000));
+        assert!(!subset_dp_fits(1, 1 << 27));
+        assert_eq!(
+            subset_query(&[1 << 27], Budget::Exactly(1 << 27)),
+            Ok(Some(vec![0]))
+        );
+
+        let many = vec![scale; SUBSET_MITM_MAX_ELVES + 1];
+        assert_eq!(
+            subset_query(&many, Budget::Exactly(scale)),
+            Err(CalorieError::SearchTooLarge {
+                elves: SUBSET_MITM_MAX_ELVES + 1,
+                target: scale
+            })
+        );
+    }
+
+    #[test]
+    fn annotated_test() {
+        let annotated = "// supplies for the expedition
+# elf: Alice
+1000
+2000
+
+// nobody
+
+#elf:Bob
+// counted twice
+4000
+
+5000
+6000";
+        let elves = parse_elves(annotated).unwrap();
+        assert_eq!(labels(&elves), vec!["Alice", "Bob", "elf 2"]);
+        assert_eq!(elves[1].items, vec![4000]);
+        assert_eq!(day1_part1(annotated), Ok(11000));
+        assert_eq!(top_n_calories(annotated, 1), Ok(vec![(2, 11000)]));
+
+        let stats = InventoryStats::new(&elves).to_string();
+        assert!(stats.contains("Heaviest: elf 2 (11000)"));
+        assert!(stats.contains("Lightest: Alice (3000)"));
+
+        let misplaced = parse_elves("1000\n# elf: Carol\n2000");
+        assert!(matches!(
+            misplaced,
+            Err(CalorieError::Parse(ParseError { line: 2, .. }))
+        ));
+        let unnamed = parse_elves("# elf:   \n1000");
+        assert!(matches!(
+            unnamed,
+            Err(CalorieError::Parse(ParseError {
+                line: 1,
+                column: 10,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn top_n_test() {
+        assert_eq!(top_n_calories(INPUT, 2), Ok(vec![(3, 24000), (2, 11000)]));
+        assert_eq!(top_n_calories(INPUT, 0), Ok(vec![]));
+        assert_eq!(top_n_calories(INPUT, 5).unwrap().last(), Some(&(1, 4000)));
+        assert_eq!(
+            top_n_calories(INPUT, 6),
+            Err(CalorieError::TooFewElves {
+                wanted: 6,
+                found: 5
+            })
+        );
     }
 }
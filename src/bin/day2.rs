@@ -1,24 +1,152 @@
-use std::convert::Into;
+use std::{collections::HashMap, fmt, time::Instant};
+
+use aoc2022::{
+    config::{Config, PROJECT_CONFIG},
+    parse::{self, ParseError, ParseResult, Parser},
+};
 
 fn main() {
     let input = include_str!("../../inputs/input2.txt");
+    let args = std::env::args().collect::<Vec<String>>();
+    let game = match args.iter().position(|arg| arg == "--game") {
+        Some(n) => match args.get(n + 1).map(String::as_str) {
+            Some("classic") => Game::classic(),
+            Some("lizard-spock") => Game::lizard_spock(),
+            other => panic!("Unknown game {other:?}"),
+        },
+        None => Game::classic(),
+    };
+    let config_path = match args.iter().position(|arg| arg == "--config") {
+        Some(n) => args[n + 1].as_str(),
+        None => PROJECT_CONFIG,
+    };
+    let scoring = Scoring::from_config(&Config::load(config_path).unwrap()).unwrap();
+    let game = game.with_scoring(scoring).unwrap();
 
-    let rounds: Vec<Vec<Move>> = input
-        .clone()
-        .lines()
-        .map(|line| line.split(' ').map(Into::into).collect())
-        .collect();
+    let rounds = parse_rounds(input, &game).unwrap();
+    println!("Day2 Part1: {}", part1(&rounds, &game));
+    match part2(&rounds, &game) {
+        Ok(total) => println!("Day2 Part2: {total}"),
+        Err(err) => println!("Day2 Part2: {err}"),
+    }
 
-    println!("Day2 Part1: {}", part1(&rounds));
+    if args.iter().any(|arg| arg == "--rounds") {
+        println!();
+        match RoundReport::new(&rounds, &game) {
+            Ok(report) => print!("{report}"),
+            Err(err) => println!("Cannot report rounds: {err}"),
+        }
+    }
 
-    let rounds2: Vec<(Move, Outcome)> = input
-        .lines()
-        .map(|line| {
-            let mut iter = line.splitn(2, ' ');
-            (iter.next().unwrap().into(), iter.next().unwrap().into())
-        })
-        .collect();
-    println!("Day2 Part2: {}", part2(&rounds2));
+    if args.iter().any(|arg| arg == "--strategy") {
+        let opponent = opponent_frequencies(&rounds, &game);
+        let names = |strategy: &MixedStrategy| {
+            game.shapes
+                .iter()
+                .zip(&strategy.weights)
+                .map(|(s, w)| format!("{} {:.3}", s.name, w))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let response = best_response(&game, &opponent);
+        let nash = equilibrium(&game);
+        println!();
+        println!(
+            "Best response to the guide's opponent: {} ({:.3} per round)",
+            names(&response),
+            response.value
+        );
+        println!(
+            "Equilibrium against any opponent: {} ({:.3} per round)",
+            names(&nash),
+            nash.value
+        );
+    }
+
+    if args.iter().any(|arg| arg == "--adaptive") {
+        let opponent = rounds.iter().map(|r| r.opponent).collect::<Vec<Move>>();
+        println!();
+        println!("{:<20} {}", "Guide (part1)", part1(&rounds, &game));
+        for mut player in adaptive_players() {
+            let total = simulate(player.as_mut(), &opponent, &game);
+            println!("{:<20} {total}", player.name());
+        }
+    }
+
+    if let Some(n) = args.iter().position(|arg| arg == "--tournament") {
+        let rounds_per_match = args.get(n + 1).and_then(|r| r.parse().ok()).unwrap_or(1000);
+        let mut sequences = vec![
+            (
+                "Guide".to_string(),
+                rounds.iter().map(|r| r.response).collect(),
+            ),
+            (
+                "Opponent".to_string(),
+                rounds.iter().map(|r| r.opponent).collect(),
+            ),
+        ];
+        for (i, arg) in args.iter().enumerate() {
+            if arg == "--guide" {
+                let path = &args[i + 1];
+                let text = std::fs::read_to_string(path).unwrap();
+                let guide = parse_rounds(&text, &game).unwrap();
+                sequences.push((path.clone(), guide.iter().map(|r| r.response).collect()));
+            }
+        }
+        println!();
+        let mut entrants = Vec::new();
+        for (name, moves) in sequences {
+            match Entrant::sequence(&name, moves) {
+                Some(entrant) => entrants.push(entrant),
+                None => println!("Skipping {name}: it has no moves"),
+            }
+        }
+        entrants.extend(Entrant::adaptive());
+        print!("{}", round_robin(&entrants, rounds_per_match, &game));
+    }
+
+    if args.iter().any(|arg| arg == "--decode") {
+        println!();
+        print!("{}", Decoding::new(&rounds, &game));
+    }
+
+    if let Some(n) = args.iter().position(|arg| arg == "--bench") {
+        let lines = args
+            .get(n + 1)
+            .and_then(|r| r.parse().ok())
+            .unwrap_or(1_000_000);
+        let guide = input
+            .lines()
+            .cycle()
+            .take(lines)
+            .collect::<Vec<&str>>()
+            .join("\n");
+        let scorer = FastScorer::new(&game).expect("every code must be a single byte");
+
+        let start = Instant::now();
+        let rounds = parse_rounds(&guide, &game).unwrap();
+        let parsed = (part1(&rounds, &game), part2(&rounds, &game));
+        let parsed_time = start.elapsed();
+
+        let start = Instant::now();
+        let fast = scorer.score(guide.as_bytes()).unwrap();
+        let fast_time = start.elapsed();
+
+        assert_eq!(parsed, fast);
+        println!();
+        match fast.1 {
+            Ok(part2) => println!("Bench: {lines} rounds, answers {} / {part2}", fast.0),
+            Err(err) => println!("Bench: {lines} rounds, answers {} / {err}", fast.0),
+        }
+        for (name, time) in [("Parsed", parsed_time), ("Bytes", fast_time)] {
+            println!(
+                "{:<8} {:>10.3} ms {:>10.1} Mrounds/s",
+                name,
+                time.as_secs_f64() * 1e3,
+                lines as f64 / time.as_secs_f64() / 1e6
+            );
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -28,109 +156,1109 @@ enum Outcome {
     Win,
 }
 
-impl From<&str> for Outcome {
-    fn from(s: &str) -> Self {
-        match s {
-            "X" => Self::Lose,
-            "Y" => Self::Draw,
-            "Z" => Self::Win,
-            _ => {
-                panic!("Unsupported outcome");
+impl Outcome {
+    const ALL: [Outcome; 3] = [Outcome::Lose, Outcome::Draw, Outcome::Win];
+
+    /// Letters of the second column when it is read as the outcome.
+    const CODES: [&'static str; 3] = ["X", "Y", "Z"];
+
+    fn from_code(code: &str) -> Option<Self> {
+        let i = Self::CODES.iter().position(|&c| c == code)?;
+        Some(Self::ALL[i])
+    }
+}
+
+/// Points awarded per round, on top of the rules of a [`Game`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Scoring {
+    lose: i32,
+    draw: i32,
+    win: i32,
+    /// Replacement shape scores, by shape name.
+    shapes: HashMap<String, i32>,
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Self {
+            lose: 0,
+            draw: 3,
+            win: 6,
+            shapes: HashMap::new(),
+        }
+    }
+}
+
+impl Scoring {
+    /// Read `[day2.scoring]` and `[day2.scoring.shapes]`, keeping the
+    /// defaults for anything left out.
+    fn from_config(config: &Config) -> ParseResult<Self> {
+        let mut scoring = Self::default();
+        for (key, points) in [
+            ("lose", &mut scoring.lose),
+            ("draw", &mut scoring.draw),
+            ("win", &mut scoring.win),
+        ] {
+            if let Some(value) = config.get_int("day2.scoring", key)? {
+                *points = value;
             }
         }
+        for name in config.keys("day2.scoring.shapes") {
+            let value = config.get_int("day2.scoring.shapes", name)?.unwrap();
+            scoring.shapes.insert(name.to_string(), value);
+        }
+        Ok(scoring)
+    }
+
+    fn outcome(&self, outcome: Outcome) -> i32 {
+        match outcome {
+            Outcome::Lose => self.lose,
+            Outcome::Draw => self.draw,
+            Outcome::Win => self.win,
+        }
     }
 }
 
-impl From<Outcome> for i32 {
-    fn from(m: Outcome) -> Self {
-        match m {
-            Outcome::Lose => 0,
-            Outcome::Draw => 3,
-            Outcome::Win => 6,
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Shape {
+    name: String,
+    /// Letter for this shape in the opponent's column.
+    opponent: String,
+    /// Letter for this shape in our column.
+    response: String,
+    score: i32,
+}
+
+impl Shape {
+    fn new(name: &str, opponent: &str, response: &str, score: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            opponent: opponent.to_string(),
+            response: response.to_string(),
+            score,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-enum Move {
-    Rock,
-    Paper,
-    Scissor,
+#[derive(Debug, PartialEq, Eq)]
+enum GameError {
+    EvenCycle(usize),
+    SelfBeat(String),
+    Undecided(String, String),
+    DuplicateCode(String),
+    UnknownShape(String),
+    /// No shape reaches the outcome against the named one.
+    Unreachable(Outcome, String),
 }
 
-impl From<&str> for Move {
-    fn from(s: &str) -> Self {
-        match s {
-            "A" | "X" => Self::Rock,
-            "B" | "Y" => Self::Paper,
-            "C" | "Z" => Self::Scissor,
-            _ => {
-                panic!("Unsupported move");
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EvenCycle(n) => write!(f, "a cycle of {n} shapes cannot be balanced"),
+            Self::SelfBeat(a) => write!(f, "{a} cannot beat itself"),
+            Self::Undecided(a, b) => {
+                write!(f, "exactly one of {a} and {b} must beat the other")
+            }
+            Self::DuplicateCode(code) => write!(f, "code {code:?} names several shapes"),
+            Self::UnknownShape(name) => write!(f, "the game has no shape named {name}"),
+            Self::Unreachable(outcome, name) => {
+                let outcome = format!("{outcome:?}").to_lowercase();
+                write!(f, "no shape can {outcome} against {name}")
             }
         }
     }
 }
 
-impl From<Move> for i32 {
-    fn from(m: Move) -> Self {
-        match m {
-            Move::Rock => 1,
-            Move::Paper => 2,
-            Move::Scissor => 3,
+/// The shapes of a game and which one beats which.
+#[derive(Debug, Clone)]
+struct Game {
+    shapes: Vec<Shape>,
+    /// `beats[a][b]` is true when shape `a` beats shape `b`.
+    beats: Vec<Vec<bool>>,
+    scoring: Scoring,
+}
+
+impl Game {
+    /// Every pair of different shapes must be decided by exactly one entry in
+    /// `wins`, given as `(winner, loser)` indices into `shapes`.
+    fn new(shapes: Vec<Shape>, wins: &[(usize, usize)]) -> Result<Self, GameError> {
+        let n = shapes.len();
+        let mut beats = vec![vec![false; n]; n];
+        for &(winner, loser) in wins {
+            if winner == loser {
+                return Err(GameError::SelfBeat(shapes[winner].name.clone()));
+            }
+            beats[winner][loser] = true;
+        }
+
+        for a in 0..n {
+            for b in a + 1..n {
+                if beats[a][b] == beats[b][a] {
+                    return Err(GameError::Undecided(
+                        shapes[a].name.clone(),
+                        shapes[b].name.clone(),
+                    ));
+                }
+            }
+        }
+
+        // a shape may use the same letter in both columns, but not share it
+        for (a, shape) in shapes.iter().enumerate() {
+            for code in [&shape.opponent, &shape.response] {
+                if shapes[a + 1..]
+                    .iter()
+                    .any(|s| &s.opponent == code || &s.response == code)
+                {
+                    return Err(GameError::DuplicateCode(code.clone()));
+                }
+            }
+        }
+
+        // part 2 needs a shape for every outcome against every shape
+        for (b, shape) in shapes.iter().enumerate() {
+            for (outcome, wins) in [(Outcome::Win, true), (Outcome::Lose, false)] {
+                if !(0..n).any(|a| a != b && beats[a][b] == wins) {
+                    return Err(GameError::Unreachable(outcome, shape.name.clone()));
+                }
+            }
+        }
+
+        Ok(Self {
+            shapes,
+            beats,
+            scoring: Scoring::default(),
+        })
+    }
+
+    /// A balanced game where every shape beats the `(n - 1) / 2` shapes listed
+    /// before it, wrapping around.
+    fn odd_cycle(shapes: Vec<Shape>) -> Result<Self, GameError> {
+        let n = shapes.len();
+        if n.is_multiple_of(2) {
+            return Err(GameError::EvenCycle(n));
+        }
+        let wins = (0..n)
+            .flat_map(|a| (1..=n / 2).map(move |k| (a, (a + n - k) % n)))
+            .collect::<Vec<(usize, usize)>>();
+        Self::new(shapes, &wins)
+    }
+
+    fn classic() -> Self {
+        Self::odd_cycle(vec![
+            Shape::new("Rock", "A", "X", 1),
+            Shape::new("Paper", "B", "Y", 2),
+            Shape::new("Scissor", "C", "Z", 3),
+        ])
+        .unwrap()
+    }
+
+    /// Rock-Paper-Scissors-Lizard-Spock; the classic letters keep their meaning.
+    fn lizard_spock() -> Self {
+        Self::odd_cycle(vec![
+            Shape::new("Rock", "A", "X", 1),
+            Shape::new("Spock", "E", "V", 5),
+            Shape::new("Paper", "B", "Y", 2),
+            Shape::new("Lizard", "D", "W", 4),
+            Shape::new("Scissor", "C", "Z", 3),
+        ])
+        .unwrap()
+    }
+
+    fn with_scoring(mut self, scoring: Scoring) -> Result<Self, GameError> {
+        if let Some(name) = scoring
+            .shapes
+            .keys()
+            .find(|&name| self.shapes.iter().all(|s| &s.name != name))
+        {
+            return Err(GameError::UnknownShape(name.clone()));
+        }
+        self.scoring = scoring;
+        Ok(self)
+    }
+
+    fn shape_score(&self, m: Move) -> i32 {
+        let shape = &self.shapes[m.0];
+        self.scoring
+            .shapes
+            .get(&shape.name)
+            .copied()
+            .unwrap_or(shape.score)
+    }
+
+    fn parse_move(&self, p: &mut Parser) -> ParseResult<Move> {
+        let start = p.clone();
+        let code = p.word()?;
+        self.shapes
+            .iter()
+            .position(|s| s.opponent == code || s.response == code)
+            .map(Move)
+            .ok_or_else(|| start.error(format!("unsupported move {code:?}")))
+    }
+
+    fn outcome(&self, ours: Move, theirs: Move) -> Outcome {
+        if ours == theirs {
+            Outcome::Draw
+        } else if self.beats[ours.0][theirs.0] {
+            Outcome::Win
+        } else {
+            Outcome::Lose
         }
     }
 }
 
+/// A shape of some [`Game`], by index.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+struct Move(usize);
+
 impl Move {
-    fn play(self, player2: Move) -> i32 {
-        let mut result: i32 = self.into();
-        result += if self == player2 {
-            3
-        } else {
-            match (self, player2) {
-                (Move::Rock, Move::Scissor) => 6,
-                (Move::Rock, Move::Paper) => 0,
-                (Move::Scissor, Move::Paper) => 6,
-                (Move::Scissor, Move::Rock) => 0,
-                (Move::Paper, Move::Rock) => 6,
-                (Move::Paper, Move::Scissor) => 0,
-                _ => {
-                    panic!("Unreachable!");
+    fn play(self, player2: Move, game: &Game) -> i32 {
+        game.shape_score(self) + game.scoring.outcome(game.outcome(self, player2))
+    }
+
+    /// The shape to answer `self` with so the round ends in `outcome`; when
+    /// several shapes would do, the highest scoring one is picked.
+    fn answer(self, outcome: Outcome, game: &Game) -> Move {
+        (0..game.shapes.len())
+            .map(Move)
+            .filter(|&m| game.outcome(m, self) == outcome)
+            .max_by_key(|&m| m.play(self, game))
+            .expect("Game::new checks every outcome can be reached")
+    }
+
+    fn guess(self, outcome: Outcome, game: &Game) -> i32 {
+        self.answer(outcome, game).play(self, game)
+    }
+}
+
+/// One line of a strategy guide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Round {
+    opponent: Move,
+    /// The second column read as the shape we play.
+    response: Move,
+    /// The second column read as the outcome we should aim for, if it is
+    /// one of `X`, `Y` or `Z`.
+    outcome: Option<Outcome>,
+}
+
+/// A round whose second column cannot be read as an outcome, so part 2
+/// cannot be scored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MissingOutcome {
+    /// Index of the round in the guide.
+    round: usize,
+}
+
+impl fmt::Display for MissingOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "round {} has no outcome: the second column must be X, Y or Z",
+            self.round + 1
+        )
+    }
+}
+
+fn parse_rounds(input: &str, game: &Game) -> ParseResult<Vec<Round>> {
+    parse::parse_lines(input, |p| {
+        let opponent = game.parse_move(p)?;
+        p.literal(" ")?;
+        let outcome = p.clone().word().ok().and_then(Outcome::from_code);
+        let response = game.parse_move(p)?;
+        Ok(Round {
+            opponent,
+            response,
+            outcome,
+        })
+    })
+}
+
+fn part1(rounds: &[Round], game: &Game) -> i32 {
+    rounds
+        .iter()
+        .map(|r| r.response.play(r.opponent, game))
+        .sum()
+}
+
+/// The shape to play in each round when the second column is the outcome.
+fn answers(rounds: &[Round], game: &Game) -> Result<Vec<Move>, MissingOutcome> {
+    rounds
+        .iter()
+        .enumerate()
+        .map(|(round, r)| match r.outcome {
+            Some(outcome) => Ok(r.opponent.answer(outcome, game)),
+            None => Err(MissingOutcome { round }),
+        })
+        .collect()
+}
+
+fn part2(rounds: &[Round], game: &Game) -> Result<i32, MissingOutcome> {
+    let answers = answers(rounds, game)?;
+    Ok(rounds
+        .iter()
+        .zip(answers)
+        .map(|(r, ours)| ours.play(r.opponent, game))
+        .sum())
+}
+
+/// Scores raw guide bytes with a lookup table instead of parsing each round.
+/// Only games whose codes are all single bytes are supported.
+#[derive(Debug, Clone)]
+struct FastScorer {
+    /// Shape index plus one for every byte that is a code, zero otherwise.
+    codes: [u8; 256],
+    /// Outcome plus one for `X`, `Y` and `Z`, zero otherwise.
+    outcomes: [u8; 256],
+    shapes: usize,
+    /// Part 1 points, indexed by `opponent * shapes + response`.
+    part1: Vec<i32>,
+    /// Part 2 points, indexed by `opponent * 3 + outcome`.
+    part2: Vec<i32>,
+}
+
+impl FastScorer {
+    fn new(game: &Game) -> Option<Self> {
+        let mut codes = [0; 256];
+        for (i, shape) in game.shapes.iter().enumerate() {
+            for code in [&shape.opponent, &shape.response] {
+                match code.as_bytes() {
+                    &[b] => codes[b as usize] = u8::try_from(i + 1).ok()?,
+                    _ => return None,
+                }
+            }
+        }
+
+        let mut outcomes = [0; 256];
+        for (code, outcome) in Outcome::CODES.iter().zip(1..) {
+            outcomes[code.as_bytes()[0] as usize] = outcome;
+        }
+
+        let shapes = game.shapes.len();
+        let part1 = (0..shapes * shapes)
+            .map(|i| Move(i % shapes).play(Move(i / shapes), game))
+            .collect();
+        let n = Outcome::ALL.len();
+        let part2 = (0..shapes * n)
+            .map(|i| Move(i / n).guess(Outcome::ALL[i % n], game))
+            .collect();
+
+        Some(Self {
+            codes,
+            outcomes,
+            shapes,
+            part1,
+            part2,
+        })
+    }
+
+    /// Part 1 and part 2 answers for `input`, which is read the same way as
+    /// by [`parse_rounds`]. Part 1 is still scored when some second column is
+    /// not an outcome code; part 2 then reports the first such round.
+    fn score(&self, input: &[u8]) -> ParseResult<(i32, Result<i32, MissingOutcome>)> {
+        if input.is_empty() {
+            return Ok((0, Ok(0)));
+        }
+        let input = input.strip_suffix(b"\n").unwrap_or(input);
+
+        let n = Outcome::ALL.len();
+        let (mut part1, mut part2, mut missing) = (0, 0, None);
+        for (i, line) in input.split(|&b| b == b'\n').enumerate() {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            let (a, b) = match *line {
+                [a, b' ', b] => (a as usize, b as usize),
+                _ => return Err(self.line_error(i + 1, line)),
+            };
+            let (opponent, response) = match (self.codes[a], self.codes[b]) {
+                (0, _) | (_, 0) => return Err(self.line_error(i + 1, line)),
+                (opponent, response) => ((opponent - 1) as usize, (response - 1) as usize),
+            };
+            part1 += self.part1[opponent * self.shapes + response];
+            match self.outcomes[b] {
+                0 => {
+                    missing.get_or_insert(MissingOutcome { round: i });
                 }
+                outcome => part2 += self.part2[opponent * n + (outcome - 1) as usize],
+            }
+        }
+
+        Ok((part1, missing.map_or(Ok(part2), Err)))
+    }
+
+    #[cold]
+    fn line_error(&self, line_no: usize, line: &[u8]) -> ParseError {
+        let known = |i: usize| line.get(i).is_some_and(|&b| self.codes[b as usize] != 0);
+        let (column, message) = if !known(0) {
+            (1, "expected a shape code")
+        } else if line.get(1) != Some(&b' ') {
+            (2, "expected \" \"")
+        } else if !known(2) {
+            (3, "expected a shape code")
+        } else {
+            (4, "expected end of line")
+        };
+        ParseError::new(line_no, column, message)
+    }
+}
+
+/// How one round is scored under either reading of the guide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RoundScore {
+    opponent: Move,
+    ours: Move,
+    outcome: Outcome,
+    shape_points: i32,
+    outcome_points: i32,
+    running_total: i32,
+}
+
+fn score_rounds<I>(plays: I, game: &Game) -> Vec<RoundScore>
+where
+    I: IntoIterator<Item = (Move, Move)>,
+{
+    let mut running_total = 0;
+    plays
+        .into_iter()
+        .map(|(opponent, ours)| {
+            let outcome = game.outcome(ours, opponent);
+            let shape_points = game.shape_score(ours);
+            let outcome_points = game.scoring.outcome(outcome);
+            running_total += shape_points + outcome_points;
+            RoundScore {
+                opponent,
+                ours,
+                outcome,
+                shape_points,
+                outcome_points,
+                running_total,
+            }
+        })
+        .collect()
+}
+
+/// Every round of the guide scored as in part 1 and as in part 2.
+#[derive(Debug)]
+struct RoundReport<'a> {
+    game: &'a Game,
+    part1: Vec<RoundScore>,
+    part2: Vec<RoundScore>,
+}
+
+impl<'a> RoundReport<'a> {
+    fn new(rounds: &[Round], game: &'a Game) -> Result<Self, MissingOutcome> {
+        let part1 = score_rounds(rounds.iter().map(|r| (r.opponent, r.response)), game);
+        let answers = answers(rounds, game)?;
+        let part2 = score_rounds(rounds.iter().map(|r| r.opponent).zip(answers), game);
+        Ok(Self { game, part1, part2 })
+    }
+}
+
+impl fmt::Display for RoundReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |m: Move| self.game.shapes[m.0].name.as_str();
+        for (title, scores) in [("Part 1", &self.part1), ("Part 2", &self.part2)] {
+            writeln!(f, "{title}")?;
+            writeln!(
+                f,
+                "{:>6} {:<8} {:<8} {:<7} {:>5} {:>7} {:>7}",
+                "Round", "Opponent", "Us", "Outcome", "Shape", "Outcome", "Total"
+            )?;
+            for (i, r) in scores.iter().enumerate() {
+                writeln!(
+                    f,
+                    "{:>6} {:<8} {:<8} {:<7} {:>5} {:>7} {:>7}",
+                    i + 1,
+                    name(r.opponent),
+                    name(r.ours),
+                    format!("{:?}", r.outcome),
+                    r.shape_points,
+                    r.outcome_points,
+                    r.running_total
+                )?;
             }
+        }
+        Ok(())
+    }
+}
+
+/// Our score for each of our shapes (rows) against each of theirs (columns).
+fn payoff_matrix(game: &Game) -> Vec<Vec<f64>> {
+    let n = game.shapes.len();
+    (0..n)
+        .map(|ours| {
+            (0..n)
+                .map(|theirs| Move(ours).play(Move(theirs), game) as f64)
+                .collect()
+        })
+        .collect()
+}
+
+/// How often the opponent plays each shape in a strategy guide.
+fn opponent_frequencies(rounds: &[Round], game: &Game) -> Vec<f64> {
+    let mut counts = vec![0.0; game.shapes.len()];
+    for round in rounds {
+        counts[round.opponent.0] += 1.0;
+    }
+    let total = rounds.len().max(1) as f64;
+    counts.into_iter().map(|c| c / total).collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct MixedStrategy {
+    /// Probability of playing each shape of the game.
+    weights: Vec<f64>,
+    /// Expected score per round.
+    value: f64,
+}
+
+/// The pure strategy scoring best against an opponent playing each shape with
+/// the given probabilities.
+fn best_response(game: &Game, opponent: &[f64]) -> MixedStrategy {
+    let expected = payoff_matrix(game)
+        .iter()
+        .map(|row| row.iter().zip(opponent).map(|(a, q)| a * q).sum::<f64>())
+        .collect::<Vec<f64>>();
+    let best = (0..expected.len())
+        .max_by(|&a, &b| expected[a].total_cmp(&expected[b]).then(b.cmp(&a)))
+        .unwrap();
+
+    let mut weights = vec![0.0; expected.len()];
+    weights[best] = 1.0;
+    MixedStrategy {
+        weights,
+        value: expected[best],
+    }
+}
+
+const EPSILON: f64 = 1e-9;
+
+/// Maximise `c·x` subject to `a x <= b`, `x >= 0`, with `b >= 0`.
+///
+/// Returns the optimal `x` and the dual values of the constraints. Bland's
+/// rule keeps the pivoting from cycling.
+fn simplex(a: &[Vec<f64>], b: &[f64], c: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let (m, n) = (b.len(), c.len());
+    // rows: constraints with slack columns and the right hand side last
+    let mut tableau = a
+        .iter()
+        .zip(b)
+        .enumerate()
+        .map(|(i, (row, &rhs))| {
+            let mut t = row.clone();
+            t.extend((0..m).map(|j| if i == j { 1.0 } else { 0.0 }));
+            t.push(rhs);
+            t
+        })
+        .collect::<Vec<Vec<f64>>>();
+    let mut objective = c.iter().map(|&v| -v).collect::<Vec<f64>>();
+    objective.resize(n + m + 1, 0.0);
+    let mut basis = (n..n + m).collect::<Vec<usize>>();
+
+    while let Some(col) = (0..n + m).find(|&j| objective[j] < -EPSILON) {
+        let Some(row) = (0..m)
+            .filter(|&i| tableau[i][col] > EPSILON)
+            .min_by(|&i, &k| {
+                let (ri, rk) = (
+                    tableau[i][n + m] / tableau[i][col],
+                    tableau[k][n + m] / tableau[k][col],
+                );
+                ri.total_cmp(&rk).then(basis[i].cmp(&basis[k]))
+            })
+        else {
+            panic!("Unbounded linear program");
         };
 
-        result
+        let pivot = tableau[row][col];
+        tableau[row].iter_mut().for_each(|v| *v /= pivot);
+        let pivot_row = tableau[row].clone();
+        for (i, t) in tableau.iter_mut().enumerate() {
+            if i != row && t[col].abs() > EPSILON {
+                let factor = t[col];
+                t.iter_mut()
+                    .zip(&pivot_row)
+                    .for_each(|(v, p)| *v -= factor * p);
+            }
+        }
+        let factor = objective[col];
+        objective
+            .iter_mut()
+            .zip(&pivot_row)
+            .for_each(|(v, p)| *v -= factor * p);
+        basis[row] = col;
+    }
+
+    let mut x = vec![0.0; n];
+    for (row, &var) in basis.iter().enumerate() {
+        if var < n {
+            x[var] = tableau[row][n + m];
+        }
+    }
+    (x, objective[n..n + m].to_vec())
+}
+
+/// Our maximin mixed strategy: the best we can guarantee per round whatever
+/// the opponent does.
+fn equilibrium(game: &Game) -> MixedStrategy {
+    let payoff = payoff_matrix(game);
+    // make every payoff positive so the game value is too
+    let lowest = payoff
+        .iter()
+        .flatten()
+        .copied()
+        .fold(f64::INFINITY, f64::min);
+    let shift = 1.0 - lowest.min(0.0);
+    let shifted = payoff
+        .iter()
+        .map(|row| row.iter().map(|v| v + shift).collect())
+        .collect::<Vec<Vec<f64>>>();
+
+    // the opponent's side: maximise sum(y) with shifted·y <= 1; our weights
+    // are the duals of that, scaled by the value 1 / sum(y)
+    let n = payoff.len();
+    let (y, duals) = simplex(&shifted, &vec![1.0; n], &vec![1.0; n]);
+    let value = 1.0 / y.iter().sum::<f64>();
+
+    MixedStrategy {
+        weights: duals.iter().map(|d| d * value).collect(),
+        value: value - shift,
+    }
+}
+
+/// Something that picks a shape every round and learns from the result.
+trait Player {
+    fn name(&self) -> String;
+    fn choose(&mut self, game: &Game) -> Move;
+    fn observe(&mut self, ours: Move, theirs: Move, game: &Game);
+}
+
+/// A single shape the opponent is expected to play, as a distribution.
+fn certain(game: &Game, m: Move) -> Vec<f64> {
+    let mut dist = vec![0.0; game.shapes.len()];
+    dist[m.0] = 1.0;
+    dist
+}
+
+fn uniform(game: &Game) -> Vec<f64> {
+    vec![1.0 / game.shapes.len() as f64; game.shapes.len()]
+}
+
+/// Best response to how often the opponent has played each shape so far.
+#[derive(Debug, Default)]
+struct FrequencyPlayer {
+    counts: Vec<f64>,
+}
+
+impl Player for FrequencyPlayer {
+    fn name(&self) -> String {
+        "Frequency".to_string()
     }
 
-    fn guess(self, outcome: Outcome) -> i32 {
-        let outcome_val: i32 = outcome.into();
-        let result: i32 = if outcome == Outcome::Draw {
-            self.into()
+    fn choose(&mut self, game: &Game) -> Move {
+        let total = self.counts.iter().sum::<f64>();
+        let dist = if total == 0.0 {
+            uniform(game)
         } else {
-            match (self, outcome) {
-                (Move::Rock, Outcome::Lose) => Move::Scissor.into(),
-                (Move::Rock, Outcome::Win) => Move::Paper.into(),
-                (Move::Scissor, Outcome::Lose) => Move::Paper.into(),
-                (Move::Scissor, Outcome::Win) => Move::Rock.into(),
-                (Move::Paper, Outcome::Lose) => Move::Rock.into(),
-                (Move::Paper, Outcome::Win) => Move::Scissor.into(),
-                _ => {
-                    panic!("Unreachable!");
+            self.counts.iter().map(|c| c / total).collect()
+        };
+        best_move(game, &dist)
+    }
+
+    fn observe(&mut self, _: Move, theirs: Move, game: &Game) {
+        self.counts.resize(game.shapes.len(), 0.0);
+        self.counts[theirs.0] += 1.0;
+    }
+}
+
+/// Predicts the opponent's next shape from what followed their last `order`
+/// shapes before, falling back to plain frequencies for unseen contexts.
+#[derive(Debug)]
+struct MarkovPlayer {
+    order: usize,
+    history: Vec<Move>,
+    transitions: HashMap<Vec<Move>, Vec<f64>>,
+    fallback: FrequencyPlayer,
+}
+
+impl MarkovPlayer {
+    fn new(order: usize) -> Self {
+        Self {
+            order,
+            history: Vec::new(),
+            transitions: HashMap::new(),
+            fallback: FrequencyPlayer::default(),
+        }
+    }
+
+    fn context(&self) -> Option<&[Move]> {
+        let start = self.history.len().checked_sub(self.order)?;
+        Some(&self.history[start..])
+    }
+}
+
+impl Player for MarkovPlayer {
+    fn name(&self) -> String {
+        format!("Markov (order {})", self.order)
+    }
+
+    fn choose(&mut self, game: &Game) -> Move {
+        match self.context().and_then(|ctx| self.transitions.get(ctx)) {
+            Some(counts) => {
+                let total = counts.iter().sum::<f64>();
+                let dist = counts.iter().map(|c| c / total).collect::<Vec<f64>>();
+                best_move(game, &dist)
+            }
+            None => self.fallback.choose(game),
+        }
+    }
+
+    fn observe(&mut self, ours: Move, theirs: Move, game: &Game) {
+        if let Some(ctx) = self.context().map(<[Move]>::to_vec) {
+            let counts = self
+                .transitions
+                .entry(ctx)
+                .or_insert_with(|| vec![0.0; game.shapes.len()]);
+            counts[theirs.0] += 1.0;
+        }
+        self.history.push(theirs);
+        self.fallback.observe(ours, theirs, game);
+    }
+}
+
+/// Keeps a winning shape; after a loss or draw switches to whatever would
+/// have beaten the opponent's last shape.
+#[derive(Debug, Default)]
+struct WinStayLoseShift {
+    next: Option<Move>,
+}
+
+impl Player for WinStayLoseShift {
+    fn name(&self) -> String {
+        "Win-stay/lose-shift".to_string()
+    }
+
+    fn choose(&mut self, game: &Game) -> Move {
+        self.next.unwrap_or_else(|| best_move(game, &uniform(game)))
+    }
+
+    fn observe(&mut self, ours: Move, theirs: Move, game: &Game) {
+        self.next = Some(match game.outcome(ours, theirs) {
+            Outcome::Win => ours,
+            _ => best_move(game, &certain(game, theirs)),
+        });
+    }
+}
+
+fn best_move(game: &Game, opponent: &[f64]) -> Move {
+    let response = best_response(game, opponent);
+    Move(response.weights.iter().position(|&w| w == 1.0).unwrap())
+}
+
+fn adaptive_players() -> Vec<Box<dyn Player>> {
+    vec![
+        Box::new(FrequencyPlayer::default()),
+        Box::new(MarkovPlayer::new(1)),
+        Box::new(MarkovPlayer::new(2)),
+        Box::new(MarkovPlayer::new(3)),
+        Box::new(WinStayLoseShift::default()),
+    ]
+}
+
+/// Total score of `player` against a fixed sequence of opponent shapes.
+fn simulate(player: &mut dyn Player, opponent: &[Move], game: &Game) -> i32 {
+    opponent
+        .iter()
+        .map(|&theirs| {
+            let ours = player.choose(game);
+            player.observe(ours, theirs, game);
+            ours.play(theirs, game)
+        })
+        .sum()
+}
+
+/// Plays a fixed list of shapes, starting over when it runs out.
+#[derive(Debug)]
+struct SequencePlayer {
+    name: String,
+    moves: Vec<Move>,
+    next: usize,
+}
+
+impl Player for SequencePlayer {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn choose(&mut self, _: &Game) -> Move {
+        let m = self.moves[self.next % self.moves.len()];
+        self.next += 1;
+        m
+    }
+
+    fn observe(&mut self, _: Move, _: Move, _: &Game) {}
+}
+
+/// A named way to build a fresh player for every match.
+struct Entrant {
+    name: String,
+    make: Box<dyn Fn() -> Box<dyn Player>>,
+}
+
+impl Entrant {
+    /// Replays `moves` over and over; there must be at least one.
+    fn sequence(name: &str, moves: Vec<Move>) -> Option<Self> {
+        if moves.is_empty() {
+            return None;
+        }
+        let player_name = name.to_string();
+        Some(Self {
+            name: name.to_string(),
+            make: Box::new(move || {
+                Box::new(SequencePlayer {
+                    name: player_name.clone(),
+                    moves: moves.clone(),
+                    next: 0,
+                })
+            }),
+        })
+    }
+
+    fn adaptive() -> Vec<Self> {
+        (0..adaptive_players().len())
+            .map(|i| Self {
+                name: adaptive_players()[i].name(),
+                make: Box::new(move || adaptive_players().swap_remove(i)),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Standing {
+    name: String,
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    /// Three for a match won, one for a draw.
+    points: u32,
+    /// Round scores summed over every match.
+    score: i32,
+}
+
+#[derive(Debug)]
+struct Tournament {
+    names: Vec<String>,
+    /// `head_to_head[a][b]` is what `a` scored in its match against `b`.
+    head_to_head: Vec<Vec<i32>>,
+}
+
+/// Every entrant plays every other once, for `rounds` rounds per match.
+fn round_robin(entrants: &[Entrant], rounds: usize, game: &Game) -> Tournament {
+    let n = entrants.len();
+    let mut head_to_head = vec![vec![0; n]; n];
+
+    for a in 0..n {
+        for b in a + 1..n {
+            let (mut pa, mut pb) = ((entrants[a].make)(), (entrants[b].make)());
+            for _ in 0..rounds {
+                let (ma, mb) = (pa.choose(game), pb.choose(game));
+                pa.observe(ma, mb, game);
+                pb.observe(mb, ma, game);
+                head_to_head[a][b] += ma.play(mb, game);
+                head_to_head[b][a] += mb.play(ma, game);
+            }
+        }
+    }
+
+    Tournament {
+        names: entrants.iter().map(|e| e.name.clone()).collect(),
+        head_to_head,
+    }
+}
+
+impl Tournament {
+    /// League table, best first.
+    fn standings(&self) -> Vec<Standing> {
+        let n = self.names.len();
+        let mut table = (0..n)
+            .map(|a| {
+                let mut s = Standing {
+                    name: self.names[a].clone(),
+                    ..Standing::default()
+                };
+                for b in (0..n).filter(|&b| b != a) {
+                    let (ours, theirs) = (self.head_to_head[a][b], self.head_to_head[b][a]);
+                    match ours.cmp(&theirs) {
+                        std::cmp::Ordering::Greater => s.wins += 1,
+                        std::cmp::Ordering::Equal => s.draws += 1,
+                        std::cmp::Ordering::Less => s.losses += 1,
+                    }
+                    s.score += ours;
+                }
+                s.points = 3 * s.wins + s.draws;
+                s
+            })
+            .collect::<Vec<Standing>>();
+        table.sort_by(|x, y| y.points.cmp(&x.points).then(y.score.cmp(&x.score)));
+        table
+    }
+}
+
+impl fmt::Display for Tournament {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(a, name)| format!("#{} {name}", a + 1))
+            .collect::<Vec<String>>();
+        let width = labels.iter().map(String::len).max().unwrap_or(0);
+        writeln!(f, "{:<width$}    W    D    L  Pts    Score", "")?;
+        for s in self.standings() {
+            writeln!(
+                f,
+                "{:<width$} {:>4} {:>4} {:>4} {:>4} {:>8}",
+                s.name, s.wins, s.draws, s.losses, s.points, s.score
+            )?;
+        }
+
+        writeln!(f)?;
+        write!(f, "{:<width$}", "")?;
+        for b in 0..self.names.len() {
+            write!(f, " {:>8}", format!("#{}", b + 1))?;
+        }
+        writeln!(f)?;
+        for (a, row) in self.head_to_head.iter().enumerate() {
+            write!(f, "{:<width$}", labels[a])?;
+            for (b, score) in row.iter().enumerate() {
+                if a == b {
+                    write!(f, " {:>8}", "-")?;
+                } else {
+                    write!(f, " {score:>8}")?;
                 }
             }
-        };
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every ordering of `0..n`, in lexicographic order.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    let mut all = Vec::new();
+    for first in 0..n {
+        for rest in permutations(n - 1) {
+            let rest = rest.into_iter().map(|x| x + usize::from(x >= first));
+            all.push(std::iter::once(first).chain(rest).collect());
+        }
+    }
+    all
+}
+
+/// Scores of every way to read the second column of a strategy guide.
+#[derive(Debug)]
+struct Decoding<'a> {
+    game: &'a Game,
+    /// Shape each response code stands for, in the order of `game.shapes`.
+    moves: Vec<(Vec<Move>, i32)>,
+    /// Outcome each of `X`, `Y` and `Z` stands for.
+    outcomes: Vec<(Vec<Outcome>, i32)>,
+}
+
+impl<'a> Decoding<'a> {
+    fn new(rounds: &[Round], game: &'a Game) -> Self {
+        let n = game.shapes.len();
+        // how often each (opponent shape, second column code) pair occurs
+        let mut move_counts = vec![vec![0; n]; n];
+        let mut outcome_counts = vec![vec![0; Outcome::ALL.len()]; n];
+        for round in rounds {
+            move_counts[round.opponent.0][round.response.0] += 1;
+            if let Some(outcome) = round.outcome {
+                outcome_counts[round.opponent.0][outcome as usize] += 1;
+            }
+        }
+
+        let moves = permutations(n)
+            .into_iter()
+            .map(|perm| {
+                let mapping = perm.into_iter().map(Move).collect::<Vec<Move>>();
+                let score = (0..n)
+                    .flat_map(|opp| (0..n).map(move |code| (opp, code)))
+                    .map(|(opp, code)| move_counts[opp][code] * mapping[code].play(Move(opp), game))
+                    .sum();
+                (mapping, score)
+            })
+            .collect();
 
-        result + outcome_val
+        let outcomes = permutations(Outcome::ALL.len())
+            .into_iter()
+            .map(|perm| {
+                let mapping = perm
+                    .into_iter()
+                    .map(|i| Outcome::ALL[i])
+                    .collect::<Vec<Outcome>>();
+                let score = (0..n)
+                    .flat_map(|opp| (0..mapping.len()).map(move |code| (opp, code)))
+                    .map(|(opp, code)| {
+                        outcome_counts[opp][code] * Move(opp).guess(mapping[code], game)
+                    })
+                    .sum();
+                (mapping, score)
+            })
+            .collect();
+
+        Self {
+            game,
+            moves,
+            outcomes,
+        }
     }
 }
 
-fn part1(rounds: &[Vec<Move>]) -> i32 {
-    rounds.iter().map(|x| x[1].play(x[0])).sum()
+/// The lowest and highest scoring entries; the first listed wins ties.
+fn extremes<T>(scored: &[(T, i32)]) -> (&(T, i32), &(T, i32)) {
+    let worst = scored.iter().min_by_key(|(_, score)| score).unwrap();
+    let best = scored.iter().rev().max_by_key(|(_, score)| score).unwrap();
+    (worst, best)
 }
 
-fn part2(rounds: &[(Move, Outcome)]) -> i32 {
-    rounds.iter().map(|x| x.0.guess(x.1)).sum()
+impl fmt::Display for Decoding<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shapes = &self.game.shapes;
+        let describe_moves = |mapping: &[Move]| {
+            shapes
+                .iter()
+                .zip(mapping)
+                .map(|(s, m)| format!("{}={}", s.response, shapes[m.0].name))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        let describe_outcomes = |mapping: &[Outcome]| {
+            ["X", "Y", "Z"]
+                .iter()
+                .zip(mapping)
+                .map(|(code, o)| format!("{code}={o:?}"))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+
+        writeln!(f, "Second column as our shape:")?;
+        for (mapping, score) in &self.moves {
+            writeln!(f, "  {}: {score}", describe_moves(mapping))?;
+        }
+        let (worst, best) = extremes(&self.moves);
+        writeln!(f, "  best:  {} ({})", describe_moves(&best.0), best.1)?;
+        writeln!(f, "  worst: {} ({})", describe_moves(&worst.0), worst.1)?;
+
+        writeln!(f, "Second column as the outcome:")?;
+        for (mapping, score) in &self.outcomes {
+            writeln!(f, "  {}: {score}", describe_outcomes(mapping))?;
+        }
+        let (worst, best) = extremes(&self.outcomes);
+        writeln!(f, "  best:  {} ({})", describe_outcomes(&best.0), best.1)?;
+        writeln!(f, "  worst: {} ({})", describe_outcomes(&worst.0), worst.1)
+    }
 }
 
 #[cfg(test)]
@@ -143,32 +1271,371 @@ C Z";
 
     #[test]
     fn test_part1() {
-        let rounds: Vec<Vec<Move>> = INPUT
-            .lines()
-            .map(|line| line.split(' ').map(Into::into).collect())
-            .collect();
+        let game = Game::classic();
+        let rounds = parse_rounds(INPUT, &game).unwrap();
 
-        assert_eq!(rounds[0][1].play(rounds[0][0]), 8);
-        assert_eq!(rounds[1][1].play(rounds[1][0]), 1);
-        assert_eq!(rounds[2][1].play(rounds[2][0]), 6);
+        assert_eq!(rounds[0].response.play(rounds[0].opponent, &game), 8);
+        assert_eq!(rounds[1].response.play(rounds[1].opponent, &game), 1);
+        assert_eq!(rounds[2].response.play(rounds[2].opponent, &game), 6);
 
-        assert_eq!(part1(&rounds), 15);
+        assert_eq!(part1(&rounds, &game), 15);
     }
 
     #[test]
     fn test_part2() {
-        let rounds: Vec<(Move, Outcome)> = INPUT
-            .lines()
-            .map(|line| {
-                let mut iter = line.splitn(2, ' ');
-                (iter.next().unwrap().into(), iter.next().unwrap().into())
+        let game = Game::classic();
+        let rounds = parse_rounds(INPUT, &game).unwrap();
+        let guess = |r: Round| r.opponent.guess(r.outcome.unwrap(), &game);
+
+        assert_eq!(guess(rounds[0]), 4);
+        assert_eq!(guess(rounds[1]), 1);
+        assert_eq!(guess(rounds[2]), 7);
+
+        assert_eq!(part2(&rounds, &game), Ok(12));
+
+        // a shape code in the second column scores in part 1 but is not an outcome
+        let rounds = parse_rounds("A Y\nB A", &game).unwrap();
+        assert_eq!(rounds[1].outcome, None);
+        assert_eq!(part1(&rounds, &game), 8 + 1);
+        assert_eq!(part2(&rounds, &game), Err(MissingOutcome { round: 1 }));
+        assert_eq!(
+            MissingOutcome { round: 1 }.to_string(),
+            "round 2 has no outcome: the second column must be X, Y or Z"
+        );
+    }
+
+    #[test]
+    fn test_fast_path() {
+        let game = Game::classic();
+        let scorer = FastScorer::new(&game).unwrap();
+        assert_eq!(scorer.score(INPUT.as_bytes()), Ok((15, Ok(12))));
+        assert_eq!(scorer.score(b"A Y\r\nB X\n"), Ok((8 + 1, Ok(4 + 1))));
+        assert_eq!(scorer.score(b""), Ok((0, Ok(0))));
+
+        // every possible round, alone and all together, must score the same
+        // both ways, for any scoring
+        let lines = ["A", "B", "C"]
+            .iter()
+            .flat_map(|a| Outcome::CODES.iter().map(move |b| format!("{a} {b}")))
+            .collect::<Vec<String>>();
+        let mut guides = lines.clone();
+        guides.push(lines.join("\n"));
+        let mut scoring = Scoring {
+            win: 10,
+            ..Scoring::default()
+        };
+        scoring.shapes.insert("Rock".to_string(), 5);
+        for game in [game.clone(), game.clone().with_scoring(scoring).unwrap()] {
+            let scorer = FastScorer::new(&game).unwrap();
+            for guide in &guides {
+                let rounds = parse_rounds(guide, &game).unwrap();
+                assert_eq!(
+                    scorer.score(guide.as_bytes()),
+                    Ok((part1(&rounds, &game), part2(&rounds, &game))),
+                    "{guide:?}"
+                );
+            }
+        }
+
+        for (bad, column) in [("A Y\nQ X", 1), ("A Y\nAX", 2), ("A Q", 3), ("A YY", 4)] {
+            let err = scorer.score(bad.as_bytes()).unwrap_err();
+            let slow = parse_rounds(bad, &game).unwrap_err();
+            assert_eq!((err.line, err.column), (slow.line, column));
+        }
+
+        // part 1 is still scored where the second column is not an outcome
+        let lizard = Game::lizard_spock();
+        let scorer = FastScorer::new(&lizard).unwrap();
+        for guide in ["A V", "A X\nB W\nC V", "A A"] {
+            let rounds = parse_rounds(guide, &lizard).unwrap();
+            assert_eq!(
+                scorer.score(guide.as_bytes()),
+                Ok((part1(&rounds, &lizard), part2(&rounds, &lizard))),
+                "{guide:?}"
+            );
+        }
+        assert_eq!(
+            scorer.score(b"A X\nB W\nC V").unwrap().1,
+            Err(MissingOutcome { round: 1 })
+        );
+    }
+
+    #[test]
+    fn test_round_report() {
+        let game = Game::classic();
+        let rounds = parse_rounds(INPUT, &game).unwrap();
+        assert_eq!(
+            rounds[0],
+            Round {
+                opponent: Move(0),
+                response: Move(1),
+                outcome: Some(Outcome::Draw),
+            }
+        );
+        assert_eq!(rounds[1].outcome, Some(Outcome::Lose));
+
+        let report = RoundReport::new(&rounds, &game).unwrap();
+        assert_eq!(
+            report.part2[2],
+            RoundScore {
+                opponent: Move(2),
+                ours: Move(0),
+                outcome: Outcome::Win,
+                shape_points: 1,
+                outcome_points: 6,
+                running_total: 12,
+            }
+        );
+        assert_eq!(
+            report.part1.last().unwrap().running_total,
+            part1(&rounds, &game)
+        );
+        assert!(report
+            .to_string()
+            .contains("     2 Paper    Rock     Lose        1       0       5"));
+    }
+
+    #[test]
+    fn test_scoring() {
+        let config = Config::parse(
+            "[day2.scoring]
+win = 10
+draw = 5
+
+[day2.scoring.shapes]
+Paper = 0",
+        )
+        .unwrap();
+        let scoring = Scoring::from_config(&config).unwrap();
+        assert_eq!(scoring.lose, 0);
+        let game = Game::classic().with_scoring(scoring).unwrap();
+
+        let rounds = parse_rounds(INPUT, &game).unwrap();
+        assert_eq!(part1(&rounds, &game), 10 + 1 + (3 + 5));
+        assert_eq!(part2(&rounds, &game), Ok((1 + 5) + 1 + (1 + 10)));
+
+        assert_eq!(
+            Scoring::from_config(&Config::default()),
+            Ok(Scoring::default())
+        );
+        let bad = Config::parse("[day2.scoring]\nwin = \"lots\"").unwrap();
+        assert_eq!(Scoring::from_config(&bad).unwrap_err().line, 2);
+        let huge = Config::parse("[day2.scoring]\ndraw = 3\nwin = 4294967302").unwrap();
+        assert_eq!(Scoring::from_config(&huge).unwrap_err().line, 3);
+        let huge = Config::parse("[day2.scoring.shapes]\nRock = -2147483649").unwrap();
+        assert_eq!(Scoring::from_config(&huge).unwrap_err().line, 2);
+
+        let mut unknown = Scoring::default();
+        unknown.shapes.insert("Spock".to_string(), 5);
+        assert_eq!(
+            Game::classic().with_scoring(unknown).unwrap_err(),
+            GameError::UnknownShape("Spock".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decoding() {
+        let game = Game::classic();
+        let decoding = Decoding::new(&parse_rounds(INPUT, &game).unwrap(), &game);
+        assert_eq!(decoding.moves.len(), 6);
+        assert_eq!(decoding.outcomes.len(), 6);
+
+        // the identity readings are the puzzle's own answers
+        assert_eq!(decoding.moves[0], (vec![Move(0), Move(1), Move(2)], 15));
+        assert_eq!(
+            decoding.outcomes[0],
+            (vec![Outcome::Lose, Outcome::Draw, Outcome::Win], 12)
+        );
+
+        let (worst, best) = extremes(&decoding.moves);
+        assert_eq!(best.1, 24);
+        assert_eq!(best.0, vec![Move(2), Move(1), Move(0)]);
+        assert_eq!(worst.1, 6);
+
+        let tied = [("a", 1), ("b", 1), ("c", 0), ("d", 0)];
+        assert_eq!(extremes(&tied), (&("c", 0), &("a", 1)));
+        assert_eq!(extremes(&tied[..2]), (&("a", 1), &("a", 1)));
+
+        let report = decoding.to_string();
+        assert!(report.contains("best:  X=Scissor Y=Paper Z=Rock (24)"));
+        assert!(report.contains("X=Lose Y=Draw Z=Win: 12"));
+
+        assert_eq!(permutations(3).len(), 6);
+        assert_eq!(permutations(5).len(), 120);
+    }
+
+    #[test]
+    fn test_strategy() {
+        let game = Game::classic();
+        let always_rock = best_response(&game, &[1.0, 0.0, 0.0]);
+        assert_eq!(always_rock.weights, vec![0.0, 1.0, 0.0]);
+        assert_eq!(always_rock.value, 8.0);
+
+        let rounds = parse_rounds(INPUT, &game).unwrap();
+        let opponent = opponent_frequencies(&rounds, &game);
+        assert_eq!(best_response(&game, &opponent).value, 6.0);
+
+        let nash = equilibrium(&game);
+        assert!((nash.value - 5.0).abs() < 1e-6);
+        assert!(nash.weights.iter().all(|w| (w - 1.0 / 3.0).abs() < 1e-6));
+
+        // whatever the opponent plays, the equilibrium earns at least its value
+        for game in [
+            Game::lizard_spock(),
+            Game::classic()
+                .with_scoring(Scoring {
+                    win: 20,
+                    ..Scoring::default()
+                })
+                .unwrap(),
+        ] {
+            let nash = equilibrium(&game);
+            assert!((nash.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+            for theirs in 0..game.shapes.len() {
+                let earned = (0..game.shapes.len())
+                    .map(|ours| nash.weights[ours] * Move(ours).play(Move(theirs), &game) as f64)
+                    .sum::<f64>();
+                assert!(earned >= nash.value - 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_adaptive() {
+        let game = Game::classic();
+        let (rock, paper, scissor) = (Move(0), Move(1), Move(2));
+
+        // uniform start favours scissors for its shape score, then counters rock
+        let opponent = [rock, rock, rock, rock];
+        let mut frequency = FrequencyPlayer::default();
+        assert_eq!(simulate(&mut frequency, &opponent, &game), 3 + 8 * 3);
+
+        // a strict cycle is fully predictable after the first lap
+        let cycle = [rock, paper, scissor].repeat(4);
+        let mut markov = MarkovPlayer::new(1);
+        let total = simulate(&mut markov, &cycle, &game);
+        let mut replay = MarkovPlayer::new(1);
+        let late = cycle
+            .iter()
+            .enumerate()
+            .map(|(i, &theirs)| {
+                let ours = replay.choose(&game);
+                replay.observe(ours, theirs, &game);
+                (i >= 4).then(|| game.outcome(ours, theirs))
             })
-            .collect();
+            .collect::<Vec<Option<Outcome>>>();
+        assert!(late.iter().flatten().all(|&o| o == Outcome::Win));
+        assert!(
+            total
+                > part1(
+                    &cycle
+                        .iter()
+                        .map(|&m| Round {
+                            opponent: m,
+                            response: m,
+                            outcome: None,
+                        })
+                        .collect::<Vec<Round>>(),
+                    &game
+                )
+        );
+
+        let mut wsls = WinStayLoseShift::default();
+        assert_eq!(wsls.choose(&game), scissor);
+        wsls.observe(scissor, rock, &game);
+        assert_eq!(wsls.choose(&game), paper);
+        wsls.observe(paper, rock, &game);
+        assert_eq!(wsls.choose(&game), paper);
+    }
+
+    #[test]
+    fn test_tournament() {
+        let game = Game::classic();
+        let entrants = vec![
+            Entrant::sequence("Rock", vec![Move(0)]).unwrap(),
+            Entrant::sequence("Paper", vec![Move(1)]).unwrap(),
+            Entrant::sequence("Cycle", vec![Move(0), Move(1), Move(2)]).unwrap(),
+        ];
+        assert!(Entrant::sequence("Empty", vec![]).is_none());
+        let tournament = round_robin(&entrants, 3, &game);
+        // paper beats rock every round; the cycle wins, draws and loses once a lap
+        assert_eq!(tournament.head_to_head[1][0], 8 * 3);
+        assert_eq!(tournament.head_to_head[0][1], 3);
+        assert_eq!(tournament.head_to_head[2][0], 4 + 8 + 3);
+        assert_eq!(tournament.head_to_head[0][2], 4 + 1 + 7);
+
+        let table = tournament.standings();
+        let names = table.iter().map(|s| s.name.as_str()).collect::<Vec<&str>>();
+        // paper and the cycle draw, paper has the better score
+        assert_eq!(names, vec!["Paper", "Cycle", "Rock"]);
+        assert_eq!((table[0].wins, table[0].draws, table[0].losses), (1, 1, 0));
+        assert_eq!(table[0].points, 4);
+        assert_eq!(table[2].points, 0);
+
+        let report = tournament.to_string();
+        assert!(report.lines().nth(1).unwrap().starts_with("Paper"));
+
+        let everyone = round_robin(&Entrant::adaptive(), 50, &game);
+        assert_eq!(everyone.standings().len(), adaptive_players().len());
+    }
+
+    #[test]
+    fn test_variants() {
+        let game = Game::lizard_spock();
+        let guide = "A Y\nE W\nD V\nC Y";
+        let rounds = parse_rounds(guide, &game).unwrap();
+        // paper covers rock, lizard poisons spock, spock loses to lizard, paper loses to scissors
+        assert_eq!(part1(&rounds, &game), 8 + 10 + 5 + 2);
+        assert_eq!(part1(&parse_rounds(INPUT, &game).unwrap(), &game), 15);
+        // losing to rock is best done with lizard
+        let guide = parse_rounds("A X", &game).unwrap();
+        assert_eq!(part2(&guide, &game), Ok(4));
 
-        assert_eq!(rounds[0].0.guess(rounds[0].1), 4);
-        assert_eq!(rounds[1].0.guess(rounds[1].1), 1);
-        assert_eq!(rounds[2].0.guess(rounds[2].1), 7);
+        let seven = Game::odd_cycle(
+            ["A", "B", "C", "D", "E", "F", "G"]
+                .iter()
+                .zip(1..)
+                .map(|(&code, score)| Shape::new(code, code, code, score))
+                .collect(),
+        )
+        .unwrap();
+        let rounds = parse_rounds("A D\nD A\nG G", &seven).unwrap();
+        assert_eq!(part1(&rounds, &seven), (4 + 6) + 1 + (7 + 3));
 
-        assert_eq!(part2(&rounds), 12);
+        let even = Game::odd_cycle(vec![Shape::new("A", "A", "A", 1); 2]);
+        assert_eq!(even.unwrap_err(), GameError::EvenCycle(2));
+        let undecided = Game::new(
+            vec![Shape::new("A", "A", "X", 1), Shape::new("B", "B", "Y", 2)],
+            &[],
+        );
+        assert_eq!(
+            undecided.unwrap_err(),
+            GameError::Undecided("A".to_string(), "B".to_string())
+        );
+        let shared = Game::new(
+            vec![Shape::new("A", "A", "X", 1), Shape::new("B", "B", "X", 2)],
+            &[(0, 1)],
+        );
+        assert_eq!(
+            shared.unwrap_err(),
+            GameError::DuplicateCode("X".to_string())
+        );
+        let single = Game::odd_cycle(vec![Shape::new("A", "A", "X", 1)]);
+        assert_eq!(
+            single.unwrap_err(),
+            GameError::Unreachable(Outcome::Win, "A".to_string())
+        );
+        // D beats everything, so nothing can win against it
+        let bully = Game::new(
+            ["A", "B", "C", "D"]
+                .iter()
+                .map(|&code| Shape::new(code, code, code, 1))
+                .collect(),
+            &[(0, 1), (1, 2), (2, 0), (3, 0), (3, 1), (3, 2)],
+        );
+        assert_eq!(
+            bully.unwrap_err(),
+            GameError::Unreachable(Outcome::Win, "D".to_string())
+        );
     }
 }
@@ -1,83 +1,755 @@
-use std::collections::HashSet;
+use std::{
+    fmt,
+    ops::{BitAnd, BitOr},
+    str::FromStr,
+};
+
+use aoc2022::parse::{self, ParseError, ParseResult, Parser};
 
 fn main() {
-    let rucksacks: Vec<Rucksack> = include_str!("../../inputs/input3.txt")
-        .lines()
-        .map(Rucksack::new)
-        .collect();
+    let input = include_str!("../../inputs/input3.txt");
+    let rucksacks: Vec<Rucksack> = parse_input(input).unwrap();
+
+    match part1(&rucksacks) {
+        Ok(total) => println!("Day3 Part1: {total}"),
+        Err(err) => println!("Day3 Part1: {err}"),
+    }
+    match part2(&rucksacks) {
+        Ok(total) => println!("Day3 Part2: {total}"),
+        Err(err) => println!("Day3 Part2: {err}"),
+    }
+
+    let args = std::env::args().collect::<Vec<String>>();
+    if let Some(n) = args.iter().position(|arg| arg == "--group") {
+        let size = args[n + 1].parse().unwrap();
+        match find_badges(&rucksacks, size) {
+            Ok(badges) => {
+                let total = badges.iter().sum::<usize>();
+                println!("Badges of {} groups of {size}: {total}", badges.len());
+            }
+            Err(err) => println!("Badges of groups of {size}: {err}"),
+        }
+    }
+
+    if args.iter().any(|arg| arg == "--validate") {
+        let problems = validate(&rucksacks);
+        println!();
+        println!(
+            "{} of {} rucksacks break the rules",
+            problems.len(),
+            rucksacks.len()
+        );
+        for (i, shared) in problems {
+            match shared.len() {
+                0 => println!("rucksack {}: no item in both compartments", i + 1),
+                n => println!(
+                    "rucksack {}: {n} items in both compartments: {shared}",
+                    i + 1
+                ),
+            }
+        }
+    }
+
+    if let Some(n) = args.iter().position(|arg| arg == "--infer-groups") {
+        let size = args.get(n + 1).and_then(|s| s.parse().ok()).unwrap_or(3);
+        println!();
+        match infer_groups(&rucksacks, size) {
+            Ok(Some(groups)) => {
+                let total = groups
+                    .iter()
+                    .map(|g| {
+                        let badge = g.iter().map(|&i| rucksacks[i].full_set());
+                        badge.fold(Items::ALL, BitAnd::bitand).first().unwrap()
+                    })
+                    .sum::<usize>();
+                println!(
+                    "Found {} groups of {size}, badges total {total}",
+                    groups.len()
+                );
+                for group in groups {
+                    let members = group.iter().map(|i| (i + 1).to_string());
+                    println!("{}", members.collect::<Vec<String>>().join(" "));
+                }
+            }
+            Ok(None) => println!("No split into groups of {size} exists"),
+            Err(err) => println!("Cannot infer groups: {err}"),
+        }
+    }
+
+    if let Some(n) = args.iter().position(|arg| arg == "--inventory") {
+        let inventory = Inventory::new(&rucksacks);
+        println!();
+        match args.get(n + 1).and_then(|s| s.chars().next()) {
+            Some(item) => {
+                let places = inventory.locations(item).iter();
+                let places = places.map(|(i, c)| format!("{}/{}", i + 1, c + 1));
+                println!(
+                    "{item} is in {} rucksacks: {}",
+                    inventory.frequency(item),
+                    places.collect::<Vec<String>>().join(" ")
+                );
+            }
+            None => print!("{inventory}"),
+        }
+    }
+
+    if args.iter().any(|arg| arg == "--reorganise") {
+        match plan_reorganisation(&rucksacks) {
+            Ok(swaps) => {
+                println!();
+                println!("{} swaps", swaps.len());
+                for swap in swaps {
+                    println!("{swap}");
+                }
+            }
+            Err(err) => println!("Cannot reorganise: {err}"),
+        }
+    }
+
+    if let Some(n) = args.iter().position(|arg| arg == "--compartments") {
+        let report = match args.get(n + 1).map(|arg| arg.parse::<usize>()) {
+            Some(Ok(1)) => compartment_report::<1>(input),
+            Some(Ok(2)) => compartment_report::<2>(input),
+            Some(Ok(3)) => compartment_report::<3>(input),
+            Some(Ok(4)) => compartment_report::<4>(input),
+            Some(Ok(other)) => format!("{other} compartments: only 1 to 4 are supported"),
+            _ => "--compartments needs a count from 1 to 4".to_string(),
+        };
+        println!("{report}");
+    }
+}
+
+/// How the input reads when every rucksack has `N` compartments.
+fn compartment_report<const N: usize>(input: &str) -> String {
+    match parse_input::<N>(input) {
+        Ok(sacks) => {
+            let total = sacks.iter().flat_map(|s| s.shared().iter()).sum::<usize>();
+            let problems = validate(&sacks).len();
+            format!(
+                "{N} compartments: {total} for items in every compartment, \
+                 {problems} rucksacks without exactly one"
+            )
+        }
+        Err(err) => format!("{N} compartments: {err}"),
+    }
+}
+
+fn parse_input<const N: usize>(input: &str) -> ParseResult<Vec<Rucksack<N>>> {
+    parse::parse_lines(input, Rucksack::parse)
+}
+
+fn part1(sacks: &[Rucksack]) -> Result<usize, NothingShared> {
+    sacks
+        .iter()
+        .enumerate()
+        .map(|(rucksack, s)| s.shared().first().ok_or(NothingShared { rucksack }))
+        .sum()
+}
+
+/// A rucksack whose compartments have no item type in common.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NothingShared {
+    /// 0-based, like the indices from [`validate`].
+    rucksack: usize,
+}
+
+impl fmt::Display for NothingShared {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rucksack {} has no item in both compartments",
+            self.rucksack + 1
+        )
+    }
+}
+
+fn part2(sacks: &[Rucksack]) -> Result<usize, BadgeError> {
+    Ok(find_badges(sacks, 3)?.iter().sum())
+}
 
-    println!("Day3 Part1: {}", part1(&rucksacks));
-    println!("Day3 Part2: {}", part2(&rucksacks));
+#[derive(Debug, PartialEq, Eq)]
+enum BadgeError {
+    ZeroGroupSize,
+    IncompleteGroup {
+        group: usize,
+        found: usize,
+        wanted: usize,
+    },
+    NoBadge {
+        group: usize,
+    },
+    SeveralBadges {
+        group: usize,
+        badges: Items,
+    },
 }
 
-fn part1(sacks: &[Rucksack]) -> usize {
+impl fmt::Display for BadgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroGroupSize => write!(f, "groups must have at least one rucksack"),
+            Self::IncompleteGroup {
+                group,
+                found,
+                wanted,
+            } => write!(f, "group {group} has {found} rucksacks instead of {wanted}"),
+            Self::NoBadge { group } => write!(f, "group {group} has no item in common"),
+            Self::SeveralBadges { group, badges } => {
+                write!(
+                    f,
+                    "group {group} has {} items in common: {badges}",
+                    badges.len()
+                )
+            }
+        }
+    }
+}
+
+/// Rucksacks whose compartments do not share exactly one item type, with the
+/// items they do share.
+fn validate<const N: usize>(sacks: &[Rucksack<N>]) -> Vec<(usize, Items)> {
     sacks
         .iter()
-        .map(|s| s.in_both().unwrap())
-        .map(|c| match c {
-            'a'..='z' => ('a'..='z').position(|x| x == c).unwrap() + 1,
-            'A'..='Z' => ('A'..='Z').position(|x| x == c).unwrap() + 27,
-            _ => 0,
+        .map(Rucksack::shared)
+        .enumerate()
+        .filter(|(_, shared)| shared.len() != 1)
+        .collect()
+}
+
+/// Priority of the one item shared by every rucksack of each consecutive
+/// group of `group_size`.
+fn find_badges<const N: usize>(
+    sacks: &[Rucksack<N>],
+    group_size: usize,
+) -> Result<Vec<usize>, BadgeError> {
+    if group_size == 0 {
+        return Err(BadgeError::ZeroGroupSize);
+    }
+
+    sacks
+        .chunks(group_size)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let group = i + 1;
+            if chunk.len() < group_size {
+                return Err(BadgeError::IncompleteGroup {
+                    group,
+                    found: chunk.len(),
+                    wanted: group_size,
+                });
+            }
+            let badges = chunk
+                .iter()
+                .map(Rucksack::full_set)
+                .fold(Items::ALL, BitAnd::bitand);
+            match badges.len() {
+                0 => Err(BadgeError::NoBadge { group }),
+                1 => Ok(badges.first().unwrap()),
+                _ => Err(BadgeError::SeveralBadges { group, badges }),
+            }
         })
-        .sum::<usize>()
+        .collect()
 }
 
-fn part2(sacks: &[Rucksack]) -> usize {
-    let mut result = 0;
-    for chunk in sacks.chunks(3) {
-        let sets = chunk
-            .iter()
-            .map(|x| x.full_set())
-            .collect::<Vec<HashSet<char>>>();
-        let value = sets[0]
-            .iter()
-            .filter(|k| sets[1].contains(k) && sets[2].contains(k))
-            .next()
-            .unwrap();
-        result += match value {
-            'a'..='z' => ('a'..='z').position(|x| x == *value).unwrap() + 1,
-            'A'..='Z' => ('A'..='Z').position(|x| x == *value).unwrap() + 27,
-            _ => 0,
+/// Backtracking search for a split of rucksacks into groups that each share
+/// exactly one item type.
+struct GroupSearch {
+    sets: Vec<Items>,
+    size: usize,
+    used: Vec<bool>,
+    groups: Vec<Vec<usize>>,
+}
+
+impl GroupSearch {
+    /// Group the lowest unused rucksack, then the rest.
+    fn solve(&mut self) -> bool {
+        let Some(first) = self.used.iter().position(|&used| !used) else {
+            return true;
         };
+        self.used[first] = true;
+        if self.extend(&mut vec![first], self.sets[first]) {
+            return true;
+        }
+        self.used[first] = false;
+        false
+    }
+
+    fn extend(&mut self, group: &mut Vec<usize>, common: Items) -> bool {
+        if group.len() == self.size {
+            if common.len() != 1 {
+                return false;
+            }
+            self.groups.push(group.clone());
+            if self.solve() {
+                return true;
+            }
+            self.groups.pop();
+            return false;
+        }
+
+        for next in group.last().unwrap() + 1..self.sets.len() {
+            let shared = common & self.sets[next];
+            if self.used[next] || shared.is_empty() {
+                continue;
+            }
+            self.used[next] = true;
+            group.push(next);
+            if self.extend(group, shared) {
+                return true;
+            }
+            group.pop();
+            self.used[next] = false;
+        }
+        false
     }
-    result
 }
 
-#[derive(Debug)]
-pub struct Rucksack {
-    pub compartment_1: String,
-    pub compartment_2: String,
+/// Split every rucksack into groups of `group_size` with exactly one badge
+/// each, without assuming groups are consecutive. `None` means no such split
+/// exists.
+fn infer_groups<const N: usize>(
+    sacks: &[Rucksack<N>],
+    group_size: usize,
+) -> Result<Option<Vec<Vec<usize>>>, BadgeError> {
+    if group_size == 0 {
+        return Err(BadgeError::ZeroGroupSize);
+    }
+    if !sacks.len().is_multiple_of(group_size) {
+        return Ok(None);
+    }
+
+    let mut search = GroupSearch {
+        sets: sacks.iter().map(Rucksack::full_set).collect(),
+        size: group_size,
+        used: vec![false; sacks.len()],
+        groups: Vec::new(),
+    };
+    Ok(search.solve().then_some(search.groups))
+}
+
+/// Item types held by at most this many rucksacks count as rare.
+const RARE_ITEMS: usize = 3;
+
+/// Priorities per line of the inventory report's distribution.
+const PRIORITY_BUCKET: usize = 13;
+
+/// Widest bar in the inventory report.
+const INVENTORY_BAR_WIDTH: usize = 40;
+
+/// Where every item type is kept, built once from all rucksacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Inventory {
+    rucksacks: usize,
+    /// `(rucksack, compartment)` pairs holding each priority, in order.
+    locations: Vec<Vec<(usize, usize)>>,
 }
 
-impl Rucksack {
-    pub fn new(contents: &str) -> Self {
-        let (comp1, comp2) = contents.split_at(contents.len() / 2);
+impl Inventory {
+    fn new<const N: usize>(sacks: &[Rucksack<N>]) -> Self {
+        let mut locations = vec![Vec::new(); 53];
+        for (i, sack) in sacks.iter().enumerate() {
+            for (c, compartment) in sack.compartments.iter().enumerate() {
+                for priority in compartment.iter() {
+                    locations[priority].push((i, c));
+                }
+            }
+        }
         Self {
-            compartment_1: comp1.to_owned(),
-            compartment_2: comp2.to_owned(),
+            rucksacks: sacks.len(),
+            locations,
         }
     }
 
-    pub fn in_both(&self) -> Option<char> {
-        let comp1: HashSet<char> = self.compartment_1.chars().collect();
-        let comp2: HashSet<char> = self.compartment_2.chars().collect();
+    /// Every `(rucksack, compartment)` holding `item`.
+    fn locations(&self, item: char) -> &[(usize, usize)] {
+        let priority = u8::try_from(item).map_or(0, priority) as usize;
+        if priority == 0 {
+            return &[];
+        }
+        &self.locations[priority]
+    }
 
-        comp1
-            .intersection(&comp2)
-            .next()
-            .and_then(|x| Some(x.to_owned()))
+    /// Rucksacks holding `item` in any compartment.
+    fn rucksacks_with(&self, item: char) -> Vec<usize> {
+        let mut sacks = self
+            .locations(item)
+            .iter()
+            .map(|&(i, _)| i)
+            .collect::<Vec<usize>>();
+        sacks.dedup();
+        sacks
     }
 
-    pub fn full_set(&self) -> HashSet<char> {
-        let comp1: HashSet<char> = self.compartment_1.chars().collect();
+    /// How many rucksacks hold `item`.
+    fn frequency(&self, item: char) -> usize {
+        self.rucksacks_with(item).len()
+    }
+
+    /// Every item type with the number of rucksacks holding it, most common
+    /// first and by priority among equals.
+    fn by_frequency(&self) -> Vec<(char, usize)> {
+        let mut counts = (1..53)
+            .map(item)
+            .map(|c| (c, self.frequency(c)))
+            .collect::<Vec<(char, usize)>>();
+        counts.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+        counts
+    }
 
-        comp1
-            .union(&self.compartment_2.chars().collect())
-            .copied()
+    /// Item types held by between one and `most` rucksacks, rarest first.
+    fn rare(&self, most: usize) -> Vec<(char, usize)> {
+        let mut rare = self.by_frequency();
+        rare.retain(|&(_, n)| (1..=most).contains(&n));
+        rare.sort_by_key(|&(_, n)| n);
+        rare
+    }
+
+    /// Item types no rucksack holds.
+    fn missing(&self) -> Items {
+        (1..53)
+            .filter(|&p| self.locations[p].is_empty())
+            .fold(Items::default(), |items, p| items | Items(1 << p))
+    }
+
+    /// Items held, counted once per rucksack, by ranges of priority.
+    fn priority_distribution(&self) -> Vec<(usize, usize, usize)> {
+        (1..53)
+            .step_by(PRIORITY_BUCKET)
+            .map(|low| {
+                let high = (low + PRIORITY_BUCKET - 1).min(52);
+                let count = (low..=high).map(|p| self.frequency(item(p))).sum();
+                (low, high, count)
+            })
             .collect()
     }
 }
 
+impl fmt::Display for Inventory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let missing = self.missing();
+        writeln!(f, "Rucksacks: {}", self.rucksacks)?;
+        writeln!(f, "Item types: {} of 52", 52 - missing.len())?;
+
+        let list = |items: &[(char, usize)]| match items {
+            [] => "none".to_string(),
+            _ => items
+                .iter()
+                .map(|(c, n)| format!("{c} ({n})"))
+                .collect::<Vec<String>>()
+                .join(", "),
+        };
+        let common = self.by_frequency();
+        writeln!(f, "Most common: {}", list(&common[..5]))?;
+        writeln!(f, "Rare: {}", list(&self.rare(RARE_ITEMS)))?;
+        if missing.is_empty() {
+            writeln!(f, "Missing: none")?;
+        } else {
+            writeln!(f, "Missing: {missing}")?;
+        }
+
+        writeln!(f, "Priorities:")?;
+        let distribution = self.priority_distribution();
+        let tallest = distribution
+            .iter()
+            .map(|&(_, _, n)| n)
+            .max()
+            .unwrap()
+            .max(1);
+        for (low, high, count) in distribution {
+            let bar = "#".repeat((count * INVENTORY_BAR_WIDTH).div_ceil(tallest));
+            writeln!(
+                f,
+                "{low:>2}-{high:>2} {}-{} | {bar} {count}",
+                item(low),
+                item(high)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Exchange of one item from the first compartment of a rucksack with one
+/// from the second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Swap {
+    rucksack: usize,
+    /// Moves from the first compartment to the second.
+    out: char,
+    /// Moves from the second compartment to the first.
+    into: char,
+}
+
+impl fmt::Display for Swap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rucksack {}: {} <-> {}",
+            self.rucksack + 1,
+            self.out,
+            self.into
+        )
+    }
+}
+
+/// A rucksack that cannot keep every item type in one compartment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PlanError {
+    /// 0-based, like [`Swap::rucksack`].
+    rucksack: usize,
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rucksack {} cannot keep every item type in one compartment",
+            self.rucksack + 1
+        )
+    }
+}
+
+/// How many of each item are in `items`, by priority.
+fn count_items(items: &str) -> ItemCounts {
+    let mut counts = [0; 53];
+    for b in items.bytes() {
+        counts[priority(b) as usize] += 1;
+    }
+    counts
+}
+
+/// The fewest swaps that leave no item type in both compartments of any
+/// rucksack.
+fn plan_reorganisation(sacks: &[Rucksack]) -> Result<Vec<Swap>, PlanError> {
+    let mut swaps = Vec::new();
+    for (rucksack, sack) in sacks.iter().enumerate() {
+        let [left, right] = &sack.counts;
+        let plan = plan_rucksack(left, right, left.iter().sum()).ok_or(PlanError { rucksack })?;
+        swaps.extend(plan.into_iter().map(|(out, into)| Swap {
+            rucksack,
+            out,
+            into,
+        }));
+    }
+    Ok(swaps)
+}
+
+/// Pick the item types that end up in the first compartment so that it still
+/// holds `size` items and as few items as possible have to come in from the
+/// second one; every item coming in is swapped for one going out.
+fn plan_rucksack(left: &ItemCounts, right: &ItemCounts, size: usize) -> Option<Vec<(char, char)>> {
+    let types = (1..53)
+        .filter(|&t| left[t] + right[t] > 0)
+        .collect::<Vec<usize>>();
+
+    // cost[i][n]: fewest items brought in when the first i types fill n slots
+    let mut cost = vec![vec![None; size + 1]; types.len() + 1];
+    cost[0][0] = Some(0);
+    for (i, &t) in types.iter().enumerate() {
+        for n in 0..=size {
+            let Some(c) = cost[i][n] else { continue };
+            let keep_right = &mut cost[i + 1][n];
+            *keep_right = Some(keep_right.map_or(c, |k: usize| k.min(c)));
+            let total = left[t] + right[t];
+            if n + total <= size {
+                let take_left = &mut cost[i + 1][n + total];
+                *take_left = Some(take_left.map_or(c + right[t], |k: usize| k.min(c + right[t])));
+            }
+        }
+    }
+    cost[types.len()][size]?;
+
+    let (mut out, mut into) = (Vec::new(), Vec::new());
+    let mut n = size;
+    for (i, &t) in types.iter().enumerate().rev() {
+        let total = left[t] + right[t];
+        let kept_left =
+            n >= total && cost[i][n - total].is_some_and(|c| Some(c + right[t]) == cost[i + 1][n]);
+        if kept_left {
+            n -= total;
+            into.extend(std::iter::repeat_n(item(t), right[t]));
+        } else {
+            out.extend(std::iter::repeat_n(item(t), left[t]));
+        }
+    }
+    out.reverse();
+    into.reverse();
+    Some(out.into_iter().zip(into).collect())
+}
+
+/// A set of item types, with each item stored at the bit of its priority.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Items(u64);
+
+impl Items {
+    /// Every priority from 0 (anything that is not a letter) to 52.
+    pub const ALL: Items = Items((1 << 53) - 1);
+
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self(bytes.iter().fold(0, |mask, &b| mask | 1 << priority(b)))
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The lowest priority in the set.
+    pub fn first(self) -> Option<usize> {
+        (!self.is_empty()).then(|| self.0.trailing_zeros() as usize)
+    }
+
+    /// Every priority in the set, lowest first.
+    pub fn iter(self) -> impl Iterator<Item = usize> {
+        let mut rest = self.0;
+        std::iter::from_fn(move || {
+            let next = Items(rest).first()?;
+            rest &= rest - 1;
+            Some(next)
+        })
+    }
+
+    /// Every item in the set, in priority order.
+    pub fn items(self) -> impl Iterator<Item = char> {
+        self.iter().map(item)
+    }
+}
+
+impl fmt::Display for Items {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.items().try_for_each(|c| write!(f, "{c}"))
+    }
+}
+
+impl BitAnd for Items {
+    type Output = Items;
+
+    fn bitand(self, rhs: Items) -> Items {
+        Items(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for Items {
+    type Output = Items;
+
+    fn bitor(self, rhs: Items) -> Items {
+        Items(self.0 | rhs.0)
+    }
+}
+
+/// `a` to `z` are 1 to 26 and `A` to `Z` are 27 to 52; anything else is 0.
+pub fn priority(item: u8) -> u32 {
+    match item {
+        b'a'..=b'z' => (item - b'a') as u32 + 1,
+        b'A'..=b'Z' => (item - b'A') as u32 + 27,
+        _ => 0,
+    }
+}
+
+/// The item with the given priority.
+pub fn item(priority: usize) -> char {
+    match priority {
+        1..=26 => (b'a' + priority as u8 - 1) as char,
+        27..=52 => (b'A' + priority as u8 - 27) as char,
+        _ => '?',
+    }
+}
+
+/// Split `s` into `N` runs of equally many characters.
+fn split_equal<const N: usize>(s: &str) -> Option<[&str; N]> {
+    let len = s.chars().count();
+    if N == 0 || len == 0 || !len.is_multiple_of(N) {
+        return None;
+    }
+
+    let size = len / N;
+    let mut rest = s;
+    Some(std::array::from_fn(|_| {
+        let at = rest.char_indices().nth(size).map_or(rest.len(), |(i, _)| i);
+        let (part, tail) = rest.split_at(at);
+        rest = tail;
+        part
+    }))
+}
+
+/// How many of each item there are, indexed by priority.
+pub type ItemCounts = [usize; 53];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rucksack<const N: usize = 2> {
+    pub compartments: [Items; N],
+    /// How many of each item every compartment holds.
+    pub counts: [ItemCounts; N],
+}
+
+/// The rest of the line as item letters, split evenly into `N` compartments.
+fn split_compartments<'a, const N: usize>(p: &mut Parser<'a>) -> ParseResult<[&'a str; N]> {
+    let start = p.clone();
+    let contents = p.rest();
+    if let Some((i, c)) = contents
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !c.is_ascii_alphabetic())
+    {
+        let column = start.column() + i;
+        return Err(ParseError::new(
+            start.line(),
+            column,
+            format!("unexpected item {c:?}"),
+        ));
+    }
+
+    split_equal::<N>(contents).ok_or_else(|| {
+        start.error(format!(
+            "{} items cannot be split into {N} equal compartments",
+            contents.chars().count()
+        ))
+    })
+}
+
+impl<const N: usize> Rucksack<N> {
+    pub fn parse(p: &mut Parser) -> ParseResult<Self> {
+        let parts = split_compartments::<N>(p)?;
+        Ok(Self {
+            compartments: parts.map(|part| Items::from_bytes(part.as_bytes())),
+            counts: parts.map(count_items),
+        })
+    }
+
+    /// Item types found in every compartment.
+    pub fn shared(&self) -> Items {
+        self.compartments.iter().fold(Items::ALL, |a, &b| a & b)
+    }
+
+    pub fn in_both(&self) -> Option<char> {
+        self.shared().first().map(item)
+    }
+
+    /// Every item found in both compartments, in priority order.
+    pub fn all_in_both(&self) -> Vec<char> {
+        self.shared().items().collect()
+    }
+
+    pub fn full_set(&self) -> Items {
+        self.compartments
+            .iter()
+            .fold(Items::default(), |a, &b| a | b)
+    }
+}
+
+impl<const N: usize> FromStr for Rucksack<N> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut p = Parser::new(s, 1);
+        let rucksack = Self::parse(&mut p)?;
+        p.end()?;
+        Ok(rucksack)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::*;
@@ -91,19 +763,201 @@ CrZsJsPPZsGzwwsLwLmpwMDw";
 
     #[test]
     fn test_part1() {
-        let rucksacks: Vec<Rucksack> = INPUT.lines().map(Rucksack::new).collect();
+        let rucksacks: Vec<Rucksack> = parse_input(INPUT).unwrap();
         assert_eq!(rucksacks[0].in_both(), Some('p'));
         assert_eq!(rucksacks[1].in_both(), Some('L'));
         assert_eq!(rucksacks[2].in_both(), Some('P'));
         assert_eq!(rucksacks[3].in_both(), Some('v'));
         assert_eq!(rucksacks[4].in_both(), Some('t'));
         assert_eq!(rucksacks[5].in_both(), Some('s'));
-        assert_eq!(part1(&rucksacks), 157);
+        assert_eq!(part1(&rucksacks), Ok(157));
+
+        let mixed: Vec<Rucksack> = parse_input("abca\nabcd").unwrap();
+        assert_eq!(part1(&mixed), Err(NothingShared { rucksack: 1 }));
+        assert_eq!(
+            NothingShared { rucksack: 1 }.to_string(),
+            "rucksack 2 has no item in both compartments"
+        );
     }
 
     #[test]
     fn test_part2() {
-        let rucksacks: Vec<Rucksack> = INPUT.lines().map(Rucksack::new).collect();
-        assert_eq!(part2(&rucksacks), 70)
+        let rucksacks: Vec<Rucksack> = parse_input(INPUT).unwrap();
+        assert_eq!(part2(&rucksacks), Ok(70))
+    }
+
+    #[test]
+    fn test_badges() {
+        let rucksacks: Vec<Rucksack> = parse_input(INPUT).unwrap();
+        assert_eq!(find_badges(&rucksacks, 3), Ok(vec![18, 52]));
+        assert_eq!(
+            find_badges(&rucksacks[..5], 3),
+            Err(BadgeError::IncompleteGroup {
+                group: 2,
+                found: 2,
+                wanted: 3
+            })
+        );
+        assert_eq!(
+            find_badges(&rucksacks, 2),
+            Err(BadgeError::SeveralBadges {
+                group: 1,
+                badges: Items::from_bytes(b"rsFMf")
+            })
+        );
+        assert_eq!(
+            find_badges(&rucksacks, 6),
+            Err(BadgeError::NoBadge { group: 1 })
+        );
+        assert_eq!(find_badges(&rucksacks, 0), Err(BadgeError::ZeroGroupSize));
+        assert_eq!(
+            find_badges(&rucksacks, 2).unwrap_err().to_string(),
+            "group 1 has 5 items in common: frsFM"
+        );
+    }
+
+    #[test]
+    fn test_shared_items() {
+        let mut rucksacks: Vec<Rucksack> = parse_input(INPUT).unwrap();
+        assert_eq!(rucksacks[0].all_in_both(), vec!['p']);
+        assert!(validate(&rucksacks).is_empty());
+
+        rucksacks.push("abCDbaDC".parse().unwrap());
+        rucksacks.push("abcd".parse().unwrap());
+        assert_eq!(rucksacks[6].all_in_both(), vec!['a', 'b', 'C', 'D']);
+        assert_eq!(
+            Items::from_bytes(b"Zza").iter().collect::<Vec<usize>>(),
+            vec![1, 26, 52]
+        );
+        assert_eq!(
+            validate(&rucksacks),
+            vec![(6, Items::from_bytes(b"abCD")), (7, Items::default())]
+        );
+    }
+
+    #[test]
+    fn test_compartments() {
+        let three: Rucksack<3> = "abcAbC".parse().unwrap();
+        assert_eq!(three.compartments[1], Items::from_bytes(b"cA"));
+        assert_eq!(three.all_in_both(), Vec::<char>::new());
+        assert_eq!("aba".parse::<Rucksack<3>>().unwrap().in_both(), None);
+
+        let err = "abc".parse::<Rucksack>().unwrap_err();
+        assert_eq!(
+            err.message,
+            "3 items cannot be split into 2 equal compartments"
+        );
+        let err = "aébc".parse::<Rucksack>().unwrap_err();
+        assert_eq!(
+            (err.column, err.message.as_str()),
+            (2, "unexpected item 'é'")
+        );
+        assert!("".parse::<Rucksack>().is_err());
+
+        assert_eq!(split_equal::<2>("aébc"), Some(["aé", "bc"]));
+        assert_eq!(split_equal::<3>("aébc"), None);
+
+        let err = parse_input::<2>("abab\nabc").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(parse_input::<4>(INPUT).unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn test_infer_groups() {
+        let rucksacks: Vec<Rucksack> = parse_input(INPUT).unwrap();
+        assert_eq!(
+            infer_groups(&rucksacks, 3),
+            Ok(Some(vec![vec![0, 1, 2], vec![3, 4, 5]]))
+        );
+
+        // interleaved groups are found too
+        let order = [3, 0, 4, 1, 5, 2];
+        let shuffled = order.map(|i| rucksacks[i]);
+        let groups = infer_groups(&shuffled, 3).unwrap().unwrap();
+        assert_eq!(groups, vec![vec![0, 2, 4], vec![1, 3, 5]]);
+
+        assert_eq!(infer_groups(&rucksacks[..5], 3), Ok(None));
+        assert_eq!(infer_groups(&rucksacks, 6), Ok(None));
+        assert_eq!(infer_groups(&rucksacks, 0), Err(BadgeError::ZeroGroupSize));
+
+        // pairing the first two sacks leaves two with nothing in common
+        let pairs: Vec<Rucksack> = parse_input("aabb\nbbcc\naaee\nccff").unwrap();
+        assert_eq!(
+            infer_groups(&pairs, 2),
+            Ok(Some(vec![vec![0, 2], vec![1, 3]]))
+        );
+        let pairs: Vec<Rucksack> = parse_input("aabb\nbbcc\naadd\neeff").unwrap();
+        assert_eq!(infer_groups(&pairs, 2), Ok(None));
+    }
+
+    #[test]
+    fn test_inventory() {
+        let rucksacks: Vec<Rucksack> = parse_input(INPUT).unwrap();
+        let inventory = Inventory::new(&rucksacks);
+        assert_eq!(inventory.locations('p'), &[(0, 0), (0, 1), (5, 1)]);
+        assert_eq!(inventory.rucksacks_with('p'), vec![0, 5]);
+        assert_eq!(inventory.frequency('r'), 4);
+        assert_eq!(inventory.frequency('x'), 0);
+        assert_eq!(inventory.locations('!'), &[]);
+        assert_eq!(inventory.by_frequency()[0], ('r', 4));
+        assert_eq!(inventory.rare(1)[..2], [('b', 1), ('d', 1)]);
+        assert_eq!(inventory.missing().to_string(), "aeiklouxyAEIKOUXY");
+        assert_eq!(
+            inventory.priority_distribution(),
+            vec![(1, 13, 16), (14, 26, 25), (27, 39, 23), (40, 52, 19)]
+        );
+        assert!(inventory.to_string().contains("Item types: 35 of 52"));
+    }
+
+    #[test]
+    fn test_reorganise() {
+        let rucksacks: Vec<Rucksack> = parse_input(INPUT).unwrap();
+        let swaps = plan_reorganisation(&rucksacks).unwrap();
+        assert_eq!(swaps.len(), 9);
+        assert_eq!(
+            swaps[0],
+            Swap {
+                rucksack: 0,
+                out: 'p',
+                into: 's'
+            }
+        );
+
+        // applying the swaps must separate every item type
+        for (i, sack) in rucksacks.iter().enumerate() {
+            let [mut left, mut right] = sack.counts;
+            for swap in swaps.iter().filter(|s| s.rucksack == i) {
+                left[priority(swap.out as u8) as usize] -= 1;
+                right[priority(swap.out as u8) as usize] += 1;
+                right[priority(swap.into as u8) as usize] -= 1;
+                left[priority(swap.into as u8) as usize] += 1;
+            }
+            assert!((1..53).all(|t| left[t] == 0 || right[t] == 0));
+        }
+
+        let plan = |input: &str| plan_reorganisation(&parse_input(input).unwrap());
+        // "aa" and "ab" can only be split as 3 + 1
+        assert_eq!(plan("abab\naaab"), Err(PlanError { rucksack: 1 }));
+        assert_eq!(
+            PlanError { rucksack: 1 }.to_string(),
+            "rucksack 2 cannot keep every item type in one compartment"
+        );
+        assert_eq!(plan("abcd"), Ok(vec![]));
+        assert_eq!(plan("aabbab").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_items() {
+        let items = Items::from_bytes(b"aZza");
+        assert_eq!(items.len(), 3);
+        assert_eq!(items.first(), Some(1));
+        assert_eq!((items & Items::from_bytes(b"Zb")).first(), Some(52));
+        assert_eq!((items | Items::from_bytes(b"b")).len(), 4);
+        assert!((items & Items::from_bytes(b"B")).is_empty());
+        assert_eq!(
+            (1..=52).map(item).map(|c| priority(c as u8)).sum::<u32>(),
+            1378
+        );
+        assert_eq!(Items::from_bytes(b"!").first(), Some(0));
     }
 }
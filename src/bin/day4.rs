@@ -1,10 +1,9 @@
-use std::{collections::HashSet, ops::RangeInclusive};
+use std::ops::RangeInclusive;
+
+use aoc2022::parse::{self, ParseResult, Parser};
 
 fn main() {
-    let assignments: Vec<AssignmentGroup> = include_str!("../../inputs/input4.txt")
-        .lines()
-        .map(AssignmentGroup::new)
-        .collect();
+    let assignments = parse_input(include_str!("../../inputs/input4.txt")).unwrap();
 
     println!("Day4 Part1: {}", part1(&assignments));
     println!("Day4 Part2: {}", part2(&assignments));
@@ -28,38 +27,42 @@ struct AssignmentGroup {
 }
 
 impl AssignmentGroup {
-    fn new(data: &str) -> Self {
-        let mut iter = data.split(&['-', ',']).map(|x| x.parse::<u32>().unwrap());
+    fn parse(p: &mut Parser) -> ParseResult<Self> {
+        let elf1 = parse_range(p)?;
+        p.separator(&[','])?;
+        let elf2 = parse_range(p)?;
 
-        Self {
-            elf1: iter.next().unwrap()..=iter.next().unwrap(),
-            elf2: iter.next().unwrap()..=iter.next().unwrap(),
-        }
+        Ok(Self { elf1, elf2 })
     }
 
+    /// One range lies entirely within the other.
     fn are_overlapping(&self) -> bool {
-        if self.elf1.contains(self.elf2.start()) && self.elf1.contains(self.elf2.end()) {
-            return true;
-        }
-        if self.elf2.contains(self.elf1.start()) && self.elf2.contains(self.elf1.end()) {
-            return true;
-        }
-
-        false
+        let (a, b) = (&self.elf1, &self.elf2);
+        (a.start() <= b.start() && b.end() <= a.end())
+            || (b.start() <= a.start() && a.end() <= b.end())
     }
 
     fn any_overlap(&self) -> bool {
-        let num_intersecting = self
-            .elf1
-            .clone()
-            .collect::<HashSet<u32>>()
-            .intersection(&self.elf2.clone().collect::<HashSet<u32>>())
-            .count();
-
-        num_intersecting > 0
+        self.elf1.start() <= self.elf2.end() && self.elf2.start() <= self.elf1.end()
     }
 }
 
+/// A non-empty `start-end` range of sections.
+fn parse_range(p: &mut Parser) -> ParseResult<RangeInclusive<u32>> {
+    let begin = p.clone();
+    let start = p.integer()?;
+    p.separator(&['-'])?;
+    let end = p.integer()?;
+    if end < start {
+        return Err(begin.error(format!("range {start}-{end} is reversed")));
+    }
+    Ok(start..=end)
+}
+
+fn parse_input(data: &str) -> ParseResult<Vec<AssignmentGroup>> {
+    parse::parse_lines(data, AssignmentGroup::parse)
+}
+
 #[cfg(test)]
 mod test {
     use crate::*;
@@ -73,13 +76,49 @@ mod test {
 
     #[test]
     fn test_part1() {
-        let assignments: Vec<AssignmentGroup> = INPUT.lines().map(AssignmentGroup::new).collect();
+        let assignments = parse_input(INPUT).unwrap();
         assert_eq!(part1(&assignments), 2);
     }
 
     #[test]
     fn test_part2() {
-        let assignments: Vec<AssignmentGroup> = INPUT.lines().map(AssignmentGroup::new).collect();
+        let assignments = parse_input(INPUT).unwrap();
         assert_eq!(part2(&assignments), 4);
     }
+
+    #[test]
+    fn test_parse() {
+        let err = parse_input("2-4,6-8\n2-4,8-2").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+        assert_eq!(err.message, "range 8-2 is reversed");
+
+        let huge = parse_input("1-4000000000,5-6").unwrap();
+        assert!(huge[0].are_overlapping());
+        assert!(huge[0].any_overlap());
+    }
+
+    #[test]
+    fn test_against_sets() {
+        use std::collections::HashSet;
+
+        let set = |r: &RangeInclusive<u32>| r.clone().collect::<HashSet<u32>>();
+        // every pair of ranges within 0..=7
+        let ranges = (0..8)
+            .flat_map(|start| (start..8).map(move |end| start..=end))
+            .collect::<Vec<RangeInclusive<u32>>>();
+
+        for (elf1, elf2) in ranges
+            .iter()
+            .flat_map(|a| ranges.iter().map(move |b| (a.clone(), b.clone())))
+        {
+            let group = AssignmentGroup { elf1, elf2 };
+            let (a, b) = (set(&group.elf1), set(&group.elf2));
+            assert_eq!(
+                group.are_overlapping(),
+                a.is_subset(&b) || b.is_subset(&a),
+                "{group:?}"
+            );
+            assert_eq!(group.any_overlap(), !a.is_disjoint(&b), "{group:?}");
+        }
+    }
 }
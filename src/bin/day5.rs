@@ -1,7 +1,10 @@
-use std::convert::Into;
+use aoc2022::{
+    grid::Grid,
+    parse::{self, ParseResult, Parser},
+};
 
 fn main() {
-    let (ship, action) = process_input(include_str!("../../inputs/input5.txt"));
+    let (ship, action) = process_input(include_str!("../../inputs/input5.txt")).unwrap();
     println!("Day5 Part1: {}", part1(&ship, &action));
     println!("Day5 Part2: {}", part2(&ship, &action));
 }
@@ -37,22 +40,19 @@ struct Ship {
 
 impl Ship {
     fn new(map_str: &[&str]) -> Self {
-        let mut map_str = map_str.to_owned();
-        let mut map: Vec<String> = Vec::new();
-
-        let last_line = map_str.pop().unwrap();
-        for (x, c) in last_line.chars().enumerate() {
-            let mut stack: String = String::new();
-            if c.is_numeric() {
-                for y in (0..map_str.len()).rev() {
-                    let id = map_str[y].chars().nth(x).unwrap();
-                    if id.is_alphabetic() {
-                        stack.push(id);
-                    }
-                }
-                map.push(stack);
-            }
-        }
+        let drawing = Grid::parse_padded(&map_str.join("\n"), ' ');
+        let labels = drawing.height() - 1;
+
+        let map = (0..drawing.width())
+            .filter(|&x| drawing[(x, labels)].is_numeric())
+            .map(|x| {
+                drawing
+                    .ray((x, labels), (0, -1))
+                    .map(|pos| drawing[pos])
+                    .filter(|c| c.is_alphabetic())
+                    .collect::<String>()
+            })
+            .collect();
 
         Self { stacks: map }
     }
@@ -80,29 +80,40 @@ enum Action {
     Move(usize, usize, usize),
 }
 
-impl From<&str> for Action {
-    fn from(s: &str) -> Self {
-        let cmd = s.split(' ').collect::<Vec<&str>>();
-        match cmd.first() {
-            Some(&"move") => Self::Move(
-                cmd[1].parse::<usize>().unwrap(),
-                cmd[3].parse::<usize>().unwrap(),
-                cmd[5].parse::<usize>().unwrap(),
-            ),
-            _ => panic!("Unknown command!"),
+impl Action {
+    fn parse(p: &mut Parser) -> ParseResult<Self> {
+        match p.word()? {
+            "move" => {
+                p.literal(" ")?;
+                let count = p.integer()?;
+                p.literal(" from ")?;
+                let from = p.integer()?;
+                p.literal(" to ")?;
+                let to = p.integer()?;
+                Ok(Self::Move(count, from, to))
+            }
+            cmd => Err(p.error(format!("unknown command {cmd:?}"))),
         }
     }
 }
 
-fn process_input(data: &str) -> (Ship, Vec<Action>) {
-    let mut lines = data.lines();
+fn process_input(data: &str) -> ParseResult<(Ship, Vec<Action>)> {
+    let mut lines = parse::lines(data);
     let map_str = lines
         .by_ref()
+        .map(|(_, line)| line)
         .take_while(|&x| !x.is_empty())
         .collect::<Vec<&str>>();
-    let actions = lines.map(Into::into).collect::<Vec<Action>>();
-
-    (Ship::new(&map_str), actions)
+    let actions = lines
+        .map(|(line_no, line)| {
+            let mut p = Parser::new(line, line_no);
+            let action = Action::parse(&mut p)?;
+            p.end()?;
+            Ok(action)
+        })
+        .collect::<ParseResult<Vec<Action>>>()?;
+
+    Ok((Ship::new(&map_str), actions))
 }
 
 #[cfg(test)]
@@ -121,13 +132,13 @@ move 1 from 1 to 2";
 
     #[test]
     fn test_part1() {
-        let (ship, action) = process_input(INPUT);
+        let (ship, action) = process_input(INPUT).unwrap();
         assert_eq!(part1(&ship, &action), "CMZ");
     }
 
     #[test]
     fn test_part2() {
-        let (ship, action) = process_input(INPUT);
+        let (ship, action) = process_input(INPUT).unwrap();
         assert_eq!(part2(&ship, &action), "MCD");
     }
 }
@@ -41,7 +41,7 @@ zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
     #[test]
     fn test_part1() {
         let inputs = INPUT.lines().collect::<Vec<&str>>();
-        assert_eq!(part1(inputs.get(0).unwrap()), 7);
+        assert_eq!(part1(inputs.first().unwrap()), 7);
         assert_eq!(part1(inputs.get(1).unwrap()), 5);
         assert_eq!(part1(inputs.get(2).unwrap()), 6);
         assert_eq!(part1(inputs.get(3).unwrap()), 10);
@@ -51,7 +51,7 @@ zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
     #[test]
     fn test_part2() {
         let inputs = INPUT.lines().collect::<Vec<&str>>();
-        assert_eq!(part2(inputs.get(0).unwrap()), 19);
+        assert_eq!(part2(inputs.first().unwrap()), 19);
         assert_eq!(part2(inputs.get(1).unwrap()), 23);
         assert_eq!(part2(inputs.get(2).unwrap()), 23);
         assert_eq!(part2(inputs.get(3).unwrap()), 29);
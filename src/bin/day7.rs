@@ -1,10 +1,12 @@
 use std::borrow::ToOwned;
 
+use aoc2022::parse::{self, ParseResult, Parser};
+
 const DAY_NUM: &str = "7";
 
 fn main() {
     let input = include_str!("../../inputs/input7.txt");
-    let tree = create_tree(input);
+    let tree = create_tree(input).unwrap();
     println!("Day{} Part1: {}", DAY_NUM, part1(&tree));
     println!("Day{} Part2: {}", DAY_NUM, part2(&tree));
 }
@@ -224,7 +226,7 @@ where
     }
 }
 
-fn create_tree(data: &str) -> ArenaTree<FileItem> {
+fn create_tree(data: &str) -> ParseResult<ArenaTree<FileItem>> {
     let mut tree: ArenaTree<FileItem> = ArenaTree::new();
     let mut current_node = tree.node(FileItem {
         name: "/".to_string(),
@@ -232,51 +234,57 @@ fn create_tree(data: &str) -> ArenaTree<FileItem> {
         file_type: FileType::Directory,
     });
 
-    for line in data.lines() {
-        let tokens = line.split(' ').collect::<Vec<&str>>();
-        if tokens[0] == "$" {
-            match tokens[1] {
-                "cd" => match tokens[2] {
-                    ".." => {
-                        if let Some(new_node) = tree.parent_of(current_node) {
-                            current_node = new_node;
+    for (line_no, line) in parse::lines(data) {
+        let mut p = Parser::new(line, line_no);
+        if p.try_literal("$ ") {
+            let command = p.clone();
+            match p.word()? {
+                "cd" => {
+                    p.literal(" ")?;
+                    let target = p.clone();
+                    match p.rest() {
+                        ".." => {
+                            if let Some(new_node) = tree.parent_of(current_node) {
+                                current_node = new_node;
+                            }
                         }
-                    }
-                    _ => {
-                        if tokens[2] == "/" {
-                            current_node = 0;
-                        } else {
-                            let Some(new_node) = tree
-                                .find_child(current_node, |fi| {
-                                    fi.name == tokens[2]
-                                }) else {
-                                    panic!("Failed to find node");
-                                };
+                        "/" => current_node = 0,
+                        name => {
+                            let Some(new_node) =
+                                tree.find_child(current_node, |fi| fi.name == name)
+                            else {
+                                return Err(target.error(format!("no directory named {name:?}")));
+                            };
                             current_node = new_node;
                         }
                     }
-                },
+                }
                 "ls" => (),
-                _ => {
-                    panic!("Unsupported command");
+                cmd => {
+                    return Err(command.error(format!("unsupported command {cmd:?}")));
                 }
             }
         } else {
-            let item = match tokens[0] {
-                "dir" => FileItem::new_dir(tokens[1]),
-                _ => FileItem::new_file(tokens[1], tokens[0].parse().unwrap()),
+            let item = if p.try_literal("dir ") {
+                FileItem::new_dir(p.rest())
+            } else {
+                let size = p.integer()?;
+                p.literal(" ")?;
+                FileItem::new_file(p.rest(), size)
             };
             tree.add_new_child(current_node, item).unwrap();
         }
+        p.end()?;
     }
     // dbg!(&tree);
 
-    tree
+    Ok(tree)
 }
 
 #[cfg(test)]
 mod test {
     use crate::*;
+    use aoc2022::search;
 
     const INPUT: &str = "$ cd /
 $ ls
@@ -304,11 +312,38 @@ $ ls
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&create_tree(INPUT)), 95437);
+        assert_eq!(part1(&create_tree(INPUT).unwrap()), 95437);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&create_tree(INPUT)), 24933642);
+        assert_eq!(part2(&create_tree(INPUT).unwrap()), 24933642);
+    }
+
+    #[test]
+    fn test_search() {
+        let tree = create_tree(INPUT).unwrap();
+        let children = |&id: &usize| tree.get_node(id).unwrap().children.clone();
+        let name = |id: usize| tree.get_node(id).unwrap().value.name.as_str();
+
+        let path = search::bfs(0, children, |&id| name(id) == "i").unwrap();
+        let names = path.into_iter().map(name).collect::<Vec<&str>>();
+        assert_eq!(names, vec!["/", "a", "e", "i"]);
+
+        let everything = search::reachable(0, children);
+        assert_eq!(
+            everything.len(),
+            1 + tree.all_child_branches(0).len() + tree.all_child_leafs(0).len()
+        );
+
+        // weight each step by the size of the file it lands on
+        let sized = |&id: &usize| {
+            children(&id)
+                .into_iter()
+                .map(|c| (c, tree.get_node(c).unwrap().value.size))
+                .collect::<Vec<_>>()
+        };
+        let (_, cost) = search::dijkstra(0, sized, |&id| name(id) == "k").unwrap();
+        assert_eq!(cost, 7214296);
     }
 }
@@ -0,0 +1,181 @@
+//! Project settings read from `aoc.toml`.
+//!
+//! Only a small part of TOML is understood: `[section]` headers, `key = value`
+//! pairs with integer or quoted string values, and `#` comments.
+
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+
+use crate::parse::{self, ParseError, ParseResult, Parser};
+
+/// Location of the project config.
+pub const PROJECT_CONFIG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/aoc.toml");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Integer(i64),
+    String(String),
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ParseError> for ConfigError {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// Values by section and key, with the line they were set on.
+    sections: BTreeMap<String, BTreeMap<String, (usize, Value)>>,
+}
+
+impl Config {
+    pub fn parse(input: &str) -> ParseResult<Self> {
+        let mut config = Self::default();
+        let mut section = String::new();
+
+        for (line_no, line) in parse::lines(input) {
+            let line = line.split('#').next().unwrap().trim_end();
+            let mut p = Parser::new(line, line_no);
+            p.take_while(char::is_whitespace);
+            if p.is_empty() {
+                continue;
+            }
+
+            if p.try_literal("[") {
+                section = p.take_while(|c| c != ']').trim().to_string();
+                p.literal("]")?;
+                p.end()?;
+                continue;
+            }
+
+            let key = p.take_while(|c| c.is_alphanumeric() || c == '_' || c == '-');
+            if key.is_empty() {
+                return Err(p.error("expected a key"));
+            }
+            p.take_while(char::is_whitespace);
+            p.literal("=")?;
+            p.take_while(char::is_whitespace);
+            let value = if p.try_literal("\"") {
+                let text = p.take_while(|c| c != '"');
+                p.literal("\"")?;
+                Value::String(text.to_string())
+            } else {
+                Value::Integer(p.integer()?)
+            };
+            p.end()?;
+
+            config
+                .sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.to_string(), (line_no, value));
+        }
+
+        Ok(config)
+    }
+
+    /// Read `path`, treating a missing file as an empty config.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(Self::parse(&text)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&Value> {
+        self.sections.get(section)?.get(key).map(|(_, value)| value)
+    }
+
+    /// All keys of `section` in sorted order.
+    pub fn keys(&self, section: &str) -> Vec<&str> {
+        self.sections
+            .get(section)
+            .map(|keys| keys.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// An integer value, which must fit in `T`.
+    pub fn get_int<T: TryFrom<i64>>(&self, section: &str, key: &str) -> ParseResult<Option<T>> {
+        match self.sections.get(section).and_then(|keys| keys.get(key)) {
+            None => Ok(None),
+            Some((line, Value::Integer(n))) => T::try_from(*n).map(Some).map_err(|_| {
+                ParseError::new(*line, 1, format!("{key} in [{section}] is out of range"))
+            }),
+            Some((line, Value::String(_))) => Err(ParseError::new(
+                *line,
+                1,
+                format!("{key} in [{section}] must be an integer"),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            "top = 1
+# comment
+[day2.scoring]
+win = 7   # was 6
+name = \"odd\"
+
+[empty]",
+        )
+        .unwrap();
+        assert_eq!(config.get_int("", "top"), Ok(Some(1)));
+        assert_eq!(config.get_int("day2.scoring", "win"), Ok(Some(7)));
+        assert_eq!(config.get_int::<i64>("day2.scoring", "lose"), Ok(None));
+        assert_eq!(
+            config.get("day2.scoring", "name"),
+            Some(&Value::String("odd".to_string()))
+        );
+        assert_eq!(config.keys("day2.scoring"), vec!["name", "win"]);
+        assert_eq!(
+            config
+                .get_int::<i64>("day2.scoring", "name")
+                .unwrap_err()
+                .line,
+            5
+        );
+        assert_eq!(config.get_int::<u8>("day2.scoring", "win"), Ok(Some(7)));
+
+        let big = Config::parse("[a]\nwin = 4294967302").unwrap();
+        assert_eq!(big.get_int("a", "win"), Ok(Some(4294967302i64)));
+        let err = big.get_int::<i32>("a", "win").unwrap_err();
+        assert_eq!(
+            (err.line, err.message.as_str()),
+            (2, "win in [a] is out of range")
+        );
+
+        let err = Config::parse("[a]\nwin = six").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 7));
+    }
+}
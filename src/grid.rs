@@ -0,0 +1,321 @@
+//! Dense 2D grid addressed by `(x, y)`, with `(0, 0)` in the top left corner.
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+use crate::parse::{self, ParseError, ParseResult};
+
+pub type Pos = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, down, left and right.
+    Four,
+    /// Orthogonal and diagonal neighbours.
+    Eight,
+}
+
+impl Connectivity {
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+            Connectivity::Eight => &[
+                (0, -1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+                (0, 1),
+                (-1, 1),
+                (-1, 0),
+                (-1, -1),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Build a grid from equally long rows.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+
+        Some(Self {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parse one cell per character; every line must be the same length.
+    pub fn parse_with<F>(input: &str, mut cell: F) -> ParseResult<Self>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut rows = Vec::new();
+        for (line_no, line) in parse::lines(input) {
+            let mut row = Vec::new();
+            for (col, c) in line.chars().enumerate() {
+                let value = cell(c).ok_or_else(|| {
+                    ParseError::new(line_no, col + 1, format!("unexpected cell {c:?}"))
+                })?;
+                row.push(value);
+            }
+            if let Some(first) = rows.first().map(Vec::len) {
+                if row.len() != first {
+                    return Err(ParseError::new(
+                        line_no,
+                        row.len().min(first) + 1,
+                        format!("expected {first} cells, found {}", row.len()),
+                    ));
+                }
+            }
+            rows.push(row);
+        }
+
+        Ok(Self::from_rows(rows).unwrap())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (x, y): Pos) -> Option<&T> {
+        if x < self.width && y < self.height {
+            self.cells.get(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, (x, y): Pos) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            self.cells.get_mut(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Step from `pos` by `delta`, if that stays on the grid.
+    pub fn offset(&self, (x, y): Pos, (dx, dy): (isize, isize)) -> Option<Pos> {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+        (x < self.width && y < self.height).then_some((x, y))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn neighbours(
+        &self,
+        pos: Pos,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = Pos> + '_ {
+        connectivity
+            .offsets()
+            .iter()
+            .filter_map(move |&delta| self.offset(pos, delta))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {x} is outside the grid");
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    /// Positions walked from `start` in steps of `delta`, not including `start`.
+    pub fn ray(&self, start: Pos, delta: (isize, isize)) -> impl Iterator<Item = Pos> + '_ {
+        std::iter::successors(self.offset(start, delta), move |&pos| {
+            self.offset(pos, delta)
+        })
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    fn from_fn<F>(width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(Pos) -> T,
+    {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut f)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.height, self.width, |(x, y)| self[(y, x)].clone())
+    }
+
+    /// Rotate a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Self
+    where
+        T: Clone,
+    {
+        let h = self.height;
+        Self::from_fn(h, self.width, |(x, y)| self[(y, h - 1 - x)].clone())
+    }
+
+    /// Rotate a quarter turn counter-clockwise.
+    pub fn rotate_ccw(&self) -> Self
+    where
+        T: Clone,
+    {
+        let w = self.width;
+        Self::from_fn(self.height, w, |(x, y)| self[(w - 1 - y, x)].clone())
+    }
+}
+
+impl Grid<char> {
+    /// Parse ragged lines, filling short rows out with `fill`.
+    pub fn parse_padded(input: &str, fill: char) -> Self {
+        let width = input.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let rows = input
+            .lines()
+            .map(|line| {
+                let mut row: Vec<char> = line.chars().collect();
+                row.resize(width, fill);
+                row
+            })
+            .collect();
+
+        Self::from_rows(rows).unwrap()
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, Some)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside the grid"))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = "abc
+def";
+
+    #[test]
+    fn test_parse_and_index() {
+        let grid: Grid<char> = INPUT.parse().unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 'f');
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.ray((0, 1), (1, -1)).collect::<Vec<_>>(), vec![(1, 0)]);
+        assert_eq!(grid.neighbours((0, 0), Connectivity::Four).count(), 2);
+        assert_eq!(grid.neighbours((1, 0), Connectivity::Eight).count(), 5);
+
+        let err = "abc\nde".parse::<Grid<char>>().unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+
+        let digits = Grid::parse_with("12\n3x", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((digits.line, digits.column), (2, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "column 3 is outside the grid")]
+    fn test_column_out_of_range() {
+        let grid: Grid<char> = INPUT.parse().unwrap();
+        grid.column(3).count();
+    }
+
+    #[test]
+    fn test_transform() {
+        let grid: Grid<char> = INPUT.parse().unwrap();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf");
+        assert_eq!(grid.rotate_cw().to_string(), "da\neb\nfc");
+        assert_eq!(grid.rotate_ccw().to_string(), "cf\nbe\nad");
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+        assert_eq!(Grid::parse_padded("a\nbcd", '.').to_string(), "a..\nbcd");
+    }
+}
@@ -0,0 +1,4 @@
+pub mod config;
+pub mod grid;
+pub mod parse;
+pub mod search;
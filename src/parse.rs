@@ -0,0 +1,288 @@
+//! Small parsing helpers shared by the daily puzzles.
+//!
+//! A [`Parser`] walks a single line and hands out literals, integers and
+//! separators. [`lines`], [`blocks`] and [`columns`] cut the puzzle input up
+//! before it gets there. Line and column numbers in errors are 1-based.
+
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// Cursor over a single line of input.
+#[derive(Debug, Clone)]
+pub struct Parser<'a> {
+    input: &'a str,
+    line: usize,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str, line: usize) -> Self {
+        Self {
+            input,
+            line,
+            pos: 0,
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Current column, counted in characters.
+    pub fn column(&self) -> usize {
+        self.input[..self.pos].chars().count() + 1
+    }
+
+    pub fn remaining(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.line, self.column(), message)
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    /// Consume `lit` if the input starts with it.
+    pub fn try_literal(&mut self, lit: &str) -> bool {
+        if self.remaining().starts_with(lit) {
+            self.pos += lit.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn literal(&mut self, lit: &str) -> ParseResult<()> {
+        if self.try_literal(lit) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {lit:?}")))
+        }
+    }
+
+    /// Consume one of `seps` and return which one matched.
+    pub fn separator(&mut self, seps: &[char]) -> ParseResult<char> {
+        match self.peek() {
+            Some(c) if seps.contains(&c) => {
+                self.pos += c.len_utf8();
+                Ok(c)
+            }
+            _ => Err(self.error(format!("expected one of {seps:?}"))),
+        }
+    }
+
+    /// Consume characters while `predicate` holds, possibly none.
+    pub fn take_while<F>(&mut self, predicate: F) -> &'a str
+    where
+        F: Fn(char) -> bool,
+    {
+        let rest = self.remaining();
+        let len = rest
+            .char_indices()
+            .find(|&(_, c)| !predicate(c))
+            .map_or(rest.len(), |(i, _)| i);
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// A non-empty run of characters up to the next space or end of line.
+    pub fn word(&mut self) -> ParseResult<&'a str> {
+        let start = self.clone();
+        let word = self.take_while(|c| !c.is_whitespace());
+        if word.is_empty() {
+            return Err(start.error("expected a word"));
+        }
+        Ok(word)
+    }
+
+    /// An optionally signed decimal integer.
+    pub fn integer<T: FromStr>(&mut self) -> ParseResult<T> {
+        let start = self.clone();
+        let sign = if self.try_literal("-") { "-" } else { "" };
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            *self = start;
+            return Err(self.error("expected an integer"));
+        }
+        let text = &start.remaining()[..sign.len() + digits.len()];
+        text.parse()
+            .map_err(|_| start.error(format!("integer {text:?} out of range")))
+    }
+
+    /// Items from `item` separated by `sep`, at least one.
+    pub fn list<T, F>(&mut self, sep: &str, mut item: F) -> ParseResult<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> ParseResult<T>,
+    {
+        let mut items = vec![item(self)?];
+        while self.try_literal(sep) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    pub fn rest(&mut self) -> &'a str {
+        let rest = self.remaining();
+        self.pos = self.input.len();
+        rest
+    }
+
+    pub fn end(&self) -> ParseResult<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("unexpected trailing input"))
+        }
+    }
+}
+
+/// Each line together with its 1-based line number.
+pub fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input.lines().enumerate().map(|(i, line)| (i + 1, line))
+}
+
+/// Parse every line of `input` with `f`, stopping at the first error.
+pub fn parse_lines<T, F>(input: &str, mut f: F) -> ParseResult<Vec<T>>
+where
+    F: FnMut(&mut Parser) -> ParseResult<T>,
+{
+    lines(input)
+        .map(|(line_no, line)| {
+            let mut parser = Parser::new(line, line_no);
+            let value = f(&mut parser)?;
+            parser.end()?;
+            Ok(value)
+        })
+        .collect()
+}
+
+/// A run of non-blank lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    /// Line number of the first line in the block.
+    pub first_line: usize,
+    pub lines: Vec<&'a str>,
+}
+
+impl<'a> Block<'a> {
+    pub fn parsers(&self) -> impl Iterator<Item = Parser<'a>> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| Parser::new(line, self.first_line + i))
+    }
+}
+
+/// Split `input` into groups separated by one or more blank lines.
+pub fn blocks(input: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Block> = None;
+
+    for (line_no, line) in lines(input) {
+        if line.is_empty() {
+            blocks.extend(current.take());
+        } else {
+            current
+                .get_or_insert_with(|| Block {
+                    first_line: line_no,
+                    lines: Vec::new(),
+                })
+                .lines
+                .push(line);
+        }
+    }
+    blocks.extend(current);
+
+    blocks
+}
+
+/// Cut `line` into fields `width` characters wide; the last may be shorter.
+pub fn columns(line: &str, width: usize) -> Vec<&str> {
+    assert!(width > 0, "column width must be positive");
+    let mut fields = Vec::new();
+    let mut start = 0;
+    for (count, (i, _)) in line.char_indices().enumerate() {
+        if count > 0 && count % width == 0 {
+            fields.push(&line[start..i]);
+            start = i;
+        }
+    }
+    if start < line.len() {
+        fields.push(&line[start..]);
+    }
+    fields
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parser() {
+        let mut p = Parser::new("move 13 from -2 to 3,4", 7);
+        p.literal("move ").unwrap();
+        assert_eq!(p.integer::<u32>(), Ok(13));
+        p.literal(" from ").unwrap();
+        assert_eq!(p.integer::<i32>(), Ok(-2));
+        assert_eq!(p.word(), Err(ParseError::new(7, 16, "expected a word")));
+        p.literal(" to ").unwrap();
+        assert_eq!(p.list(",", Parser::integer::<u8>), Ok(vec![3, 4]));
+        assert_eq!(p.end(), Ok(()));
+
+        let mut p = Parser::new("ab 300", 2);
+        assert_eq!(
+            p.integer::<u8>(),
+            Err(ParseError::new(2, 1, "expected an integer"))
+        );
+        assert_eq!(p.word(), Ok("ab"));
+        assert_eq!(p.separator(&[' ', ',']), Ok(' '));
+        let err = p.integer::<u8>().unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+        assert_eq!(err.to_string(), "2:4: integer \"300\" out of range");
+    }
+
+    #[test]
+    fn test_blocks_and_columns() {
+        let blocks = blocks("1\n2\n\n\n3\n\n4");
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].lines, vec!["1", "2"]);
+        assert_eq!(blocks[1].first_line, 5);
+        assert_eq!(blocks[2].parsers().next().unwrap().line(), 7);
+
+        assert_eq!(columns("[Z] [M] [P]", 4), vec!["[Z] ", "[M] ", "[P]"]);
+        assert_eq!(columns("", 4), Vec::<&str>::new());
+    }
+}
@@ -0,0 +1,188 @@
+//! Graph searches over implicit graphs described by a neighbour function.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// Walk back from `end` through `parents` to the node without a parent.
+pub fn reconstruct_path<N>(parents: &HashMap<N, N>, end: N) -> Vec<N>
+where
+    N: Clone + Eq + Hash,
+{
+    let mut path = vec![end];
+    while let Some(prev) = parents.get(path.last().unwrap()) {
+        path.push(prev.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Every node reachable from `start`, in breadth-first order.
+pub fn reachable<N, FN, IN>(start: N, mut neighbours: FN) -> Vec<N>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::from([start.clone()]);
+    let mut order = vec![start];
+    let mut next = 0;
+
+    while let Some(node) = order.get(next).cloned() {
+        next += 1;
+        for n in neighbours(&node) {
+            if seen.insert(n.clone()) {
+                order.push(n);
+            }
+        }
+    }
+
+    order
+}
+
+/// Shortest path by edge count from `start` to the first node matching `goal`.
+pub fn bfs<N, FN, IN, FG>(start: N, mut neighbours: FN, mut goal: FG) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FG: FnMut(&N) -> bool,
+{
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::from([start.clone()]);
+    let mut seen = HashSet::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        if goal(&node) {
+            return Some(reconstruct_path(&parents, node));
+        }
+        for n in neighbours(&node) {
+            if seen.insert(n.clone()) {
+                parents.insert(n.clone(), node.clone());
+                queue.push_back(n);
+            }
+        }
+    }
+
+    None
+}
+
+/// Cheapest path with non-negative edge costs.
+pub fn dijkstra<N, C, FN, IN, FG>(start: N, neighbours: FN, goal: FG) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FG: FnMut(&N) -> bool,
+{
+    astar(start, neighbours, |_| C::default(), goal)
+}
+
+/// Cheapest path guided by `heuristic`, which must never overestimate.
+pub fn astar<N, C, FN, IN, FH, FG>(
+    start: N,
+    mut neighbours: FN,
+    mut heuristic: FH,
+    mut goal: FG,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FG: FnMut(&N) -> bool,
+{
+    // nodes are kept in a table so the heap only has to order indices
+    let mut index: HashMap<N, usize> = HashMap::from([(start.clone(), 0)]);
+    let mut nodes: Vec<(N, Option<usize>, C)> = vec![(start.clone(), None, C::default())];
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&start), 0))]);
+
+    while let Some(Reverse((_, id))) = heap.pop() {
+        let (node, _, cost) = nodes[id].clone();
+        if goal(&node) {
+            let mut path = vec![id];
+            while let Some(parent) = nodes[*path.last().unwrap()].1 {
+                path.push(parent);
+            }
+            let path = path.into_iter().rev().map(|i| nodes[i].0.clone());
+            return Some((path.collect(), cost));
+        }
+
+        for (n, step) in neighbours(&node) {
+            let new_cost = cost + step;
+            let n_id = match index.get(&n) {
+                Some(&n_id) if nodes[n_id].2 <= new_cost => continue,
+                Some(&n_id) => {
+                    nodes[n_id].1 = Some(id);
+                    nodes[n_id].2 = new_cost;
+                    n_id
+                }
+                None => {
+                    let n_id = nodes.len();
+                    index.insert(n.clone(), n_id);
+                    nodes.push((n.clone(), Some(id), new_cost));
+                    n_id
+                }
+            };
+            heap.push(Reverse((new_cost + heuristic(&n), n_id)));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::{Connectivity, Grid, Pos};
+
+    const MAZE: &str = "S.#.....
+.##.###.
+....#9..
+.####.#E
+......#.";
+
+    fn open_neighbours(grid: &Grid<char>, pos: Pos) -> Vec<Pos> {
+        grid.neighbours(pos, Connectivity::Four)
+            .filter(|&n| grid[n] != '#')
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs_grid() {
+        let grid: Grid<char> = MAZE.parse().unwrap();
+        let path = bfs((0, 0), |&p| open_neighbours(&grid, p), |&p| grid[p] == 'E').unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(7, 3)));
+        assert_eq!(path.len() - 1, 14);
+        assert!(bfs((0, 0), |&p| open_neighbours(&grid, p), |&p| grid[p] == 'X').is_none());
+        assert_eq!(reachable((0, 0), |&p| open_neighbours(&grid, p)).len(), 27);
+    }
+
+    #[test]
+    fn test_weighted_grid() {
+        let grid: Grid<char> = MAZE.parse().unwrap();
+        let cost = |p: Pos| grid[p].to_digit(10).unwrap_or(1);
+        let weighted = |&p: &Pos| {
+            open_neighbours(&grid, p)
+                .into_iter()
+                .map(|n| (n, cost(n)))
+                .collect::<Vec<_>>()
+        };
+        let goal = (7, 3);
+        let manhattan = |&(x, y): &Pos| (x.abs_diff(goal.0) + y.abs_diff(goal.1)) as u32;
+
+        let (path, total) = dijkstra((0, 0), weighted, |&p| p == goal).unwrap();
+        assert_eq!(total, 14);
+        assert!(!path.contains(&(5, 2)));
+
+        let (astar_path, astar_total) = astar((0, 0), weighted, manhattan, |&p| p == goal).unwrap();
+        assert_eq!(astar_total, total);
+        assert_eq!(astar_path.len(), path.len());
+    }
+}